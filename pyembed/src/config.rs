@@ -27,7 +27,42 @@ pub enum PythonRunMode {
     /// We define this as a CString because the underlying API wants
     /// a char* and we want the constructor of this type to worry about
     /// the type coercion.
+    ///
+    /// A leading ``$ORIGIN`` component will resolve to the directory of the
+    /// application at run-time.
     File { path: PathBuf },
+    /// Run multiple run modes in order.
+    ///
+    /// All steps but the last are evaluated after interpreter initialization.
+    /// The last step is the main run mode and is evaluated like it would be
+    /// if it weren't part of a sequence.
+    Sequence { steps: Vec<PythonRunMode> },
+}
+
+impl PythonRunMode {
+    /// Obtain the run mode to evaluate as the main program.
+    ///
+    /// For sequences, this is the last step. This never returns a sequence.
+    pub(crate) fn main_mode(&self) -> &PythonRunMode {
+        match self {
+            PythonRunMode::Sequence { steps } => match steps.last() {
+                Some(step) => step.main_mode(),
+                None => &PythonRunMode::None,
+            },
+            mode => mode,
+        }
+    }
+
+    /// Obtain run modes to evaluate before the main program.
+    pub(crate) fn setup_modes(&self) -> Vec<&PythonRunMode> {
+        match self {
+            PythonRunMode::Sequence { steps } => match steps.split_last() {
+                Some((last, rest)) => rest.iter().chain(last.setup_modes()).collect(),
+                None => vec![],
+            },
+            _ => vec![],
+        }
+    }
 }
 
 /// Defines `terminfo`` database resolution semantics.
@@ -37,6 +72,8 @@ pub enum TerminfoResolution {
     Dynamic,
     /// Do not attempt to resolve the `terminfo` database. Basically a no-op.
     None,
+    /// Use well-known `terminfo` directories that exist, regardless of OS.
+    SystemPaths,
     /// Use a specified string as the `TERMINFO_DIRS` value.
     Static(String),
 }
@@ -76,8 +113,8 @@ pub struct PythonConfig<'a> {
 
     /// Filesystem paths to add to sys.path.
     ///
-    /// ``$ORIGIN`` will resolve to the directory of the application at
-    /// run-time.
+    /// A leading ``$ORIGIN`` or ``$APPDIR`` component will resolve to the
+    /// directory of the application at run-time.
     pub sys_paths: Vec<String>,
 
     /// Controls whether to detect comparing bytes/bytearray with str.
@@ -86,6 +123,21 @@ pub struct PythonConfig<'a> {
     /// exception.
     pub bytes_warning: i32,
 
+    /// Whether to coerce the legacy C locale to a UTF-8 based locale.
+    ///
+    /// ``None`` uses the default of the interpreter profile. If ``Some(true)``,
+    /// the ``LC_CTYPE`` locale is coerced when it is the C locale.
+    pub coerce_c_locale: Option<bool>,
+
+    /// Whether to emit a warning when the C locale is coerced.
+    pub coerce_c_locale_warn: bool,
+
+    /// Whether to enable Python development mode.
+    ///
+    /// This is equivalent to ``python -X dev``. Development mode enables the
+    /// faulthandler, which takes precedence over other settings disabling it.
+    pub development_mode: bool,
+
     /// Whether to load the site.py module at initialization time.
     pub import_site: bool,
 
@@ -98,6 +150,12 @@ pub struct PythonConfig<'a> {
     /// Whether to enter interactive mode after executing a script or a command.
     pub inspect: bool,
 
+    /// Limit on the number of digits in integer string conversions.
+    ///
+    /// ``0`` disables the limit. Only honored by Python versions supporting
+    /// the ``int_max_str_digits`` X option.
+    pub int_max_str_digits: Option<i64>,
+
     /// Whether to put interpreter in interactive mode.
     pub interactive: bool,
 
@@ -133,6 +191,13 @@ pub struct PythonConfig<'a> {
     /// Controls the level of the verbose mode for the interpreter.
     pub verbose: i32,
 
+    /// Whether to emit an ``EncodingWarning`` when the default locale-specific
+    /// encoding is used.
+    ///
+    /// This is applied via the ``warn_default_encoding`` X option and is only
+    /// honored by Python versions supporting it.
+    pub warn_default_encoding: bool,
+
     /// Reference to packed resources data.
     ///
     /// The referenced data contains Python module data. It likely comes from an
@@ -180,6 +245,14 @@ pub struct PythonConfig<'a> {
     /// loaded in ``sys.modules``.
     pub write_modules_directory_env: Option<String>,
 
+    /// Directory to write a loaded modules file to.
+    ///
+    /// If set, a ``modules-<random>`` file is always written to this directory
+    /// on interpreter shutdown. ``$ORIGIN`` will resolve to the directory of the
+    /// application at run-time. If ``write_modules_directory_env`` is also set,
+    /// that environment variable is set to this directory at startup.
+    pub write_modules_directory: Option<PathBuf>,
+
     /// Defines what code to run by default.
     ///
     pub run: PythonRunMode,
@@ -197,10 +270,14 @@ impl<'a> Default for PythonConfig<'a> {
             filesystem_importer: false,
             sys_paths: vec![],
             bytes_warning: 0,
+            coerce_c_locale: None,
+            coerce_c_locale_warn: false,
+            development_mode: false,
             import_site: false,
             import_user_site: false,
             ignore_python_env: true,
             inspect: false,
+            int_max_str_digits: None,
             interactive: false,
             isolated: false,
             legacy_windows_fs_encoding: false,
@@ -211,6 +288,7 @@ impl<'a> Default for PythonConfig<'a> {
             quiet: false,
             use_hash_seed: false,
             verbose: 0,
+            warn_default_encoding: false,
             packed_resources: &[],
             extra_extension_modules: vec![],
             argvb: false,
@@ -219,6 +297,7 @@ impl<'a> Default for PythonConfig<'a> {
             raw_allocator: PythonRawAllocator::default(),
             terminfo_resolution: TerminfoResolution::Dynamic,
             write_modules_directory_env: None,
+            write_modules_directory: None,
             run: PythonRunMode::None,
         }
     }
@@ -259,8 +338,9 @@ pub enum Allocator {
 /// Holds values for coerce_c_locale.
 ///
 /// See https://docs.python.org/3/c-api/init_config.html#c.PyPreConfig.coerce_c_locale.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum CoerceCLocale {
+    Off = 0,
     LCCtype = 1,
     C = 2,
 }
@@ -483,6 +563,8 @@ pub enum MemoryAllocatorBackend {
     System,
     /// Use jemalloc.
     Jemalloc,
+    /// Use mimalloc.
+    Mimalloc,
     /// Use Rust's global allocator.
     Rust,
 }
@@ -515,6 +597,13 @@ impl PythonRawAllocator {
         }
     }
 
+    pub fn mimalloc() -> Self {
+        Self {
+            backend: MemoryAllocatorBackend::Mimalloc,
+            ..PythonRawAllocator::default()
+        }
+    }
+
     pub fn rust() -> Self {
         Self {
             backend: MemoryAllocatorBackend::Rust,
@@ -623,6 +712,14 @@ pub struct OxidizedPythonInterpreterConfig<'a> {
     /// loaded in ``sys.modules``.
     pub write_modules_directory_env: Option<String>,
 
+    /// Directory to write a loaded modules file to.
+    ///
+    /// If set, a ``modules-<random>`` file is always written to this directory
+    /// on interpreter shutdown. ``$ORIGIN`` will resolve to the directory of the
+    /// application at run-time. If ``write_modules_directory_env`` is also set,
+    /// that environment variable is set to this directory at startup.
+    pub write_modules_directory: Option<PathBuf>,
+
     /// Defines what code to run by default.
     ///
     pub run: PythonRunMode,
@@ -645,6 +742,7 @@ impl<'a> Default for OxidizedPythonInterpreterConfig<'a> {
             sys_meipass: false,
             terminfo_resolution: TerminfoResolution::Dynamic,
             write_modules_directory_env: None,
+            write_modules_directory: None,
             run: PythonRunMode::Repl,
         }
     }
@@ -652,6 +750,16 @@ impl<'a> Default for OxidizedPythonInterpreterConfig<'a> {
 
 impl<'a> From<PythonConfig<'a>> for OxidizedPythonInterpreterConfig<'a> {
     fn from(config: PythonConfig<'a>) -> Self {
+        let mut x_options = Vec::new();
+
+        if let Some(value) = config.int_max_str_digits {
+            x_options.push(format!("int_max_str_digits={}", value));
+        }
+
+        if config.warn_default_encoding {
+            x_options.push("warn_default_encoding".to_string());
+        }
+
         Self {
             interpreter_config: PythonInterpreterConfig {
                 profile: if config.isolated {
@@ -678,6 +786,19 @@ impl<'a> From<PythonConfig<'a>> for OxidizedPythonInterpreterConfig<'a> {
                     2 => BytesWarning::Raise,
                     _ => BytesWarning::Raise,
                 }),
+                coerce_c_locale: config.coerce_c_locale.map(|v| {
+                    if v {
+                        CoerceCLocale::LCCtype
+                    } else {
+                        CoerceCLocale::Off
+                    }
+                }),
+                coerce_c_locale_warn: if config.coerce_c_locale_warn {
+                    Some(true)
+                } else {
+                    None
+                },
+                development_mode: Some(config.development_mode),
                 site_import: Some(config.import_site),
                 user_site_directory: Some(config.import_user_site),
                 use_environment: Some(!config.ignore_python_env),
@@ -690,6 +811,11 @@ impl<'a> From<PythonConfig<'a>> for OxidizedPythonInterpreterConfig<'a> {
                 parser_debug: Some(config.parser_debug),
                 quiet: Some(config.quiet),
                 verbose: Some(config.verbose != 0),
+                x_options: if x_options.is_empty() {
+                    None
+                } else {
+                    Some(x_options)
+                },
                 ..PythonInterpreterConfig::default()
             },
             raw_allocator: Some(config.raw_allocator),
@@ -702,6 +828,7 @@ impl<'a> From<PythonConfig<'a>> for OxidizedPythonInterpreterConfig<'a> {
             sys_meipass: config.sys_meipass,
             terminfo_resolution: config.terminfo_resolution,
             write_modules_directory_env: config.write_modules_directory_env,
+            write_modules_directory: config.write_modules_directory,
             run: config.run,
         }
     }
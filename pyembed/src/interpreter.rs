@@ -11,8 +11,10 @@ use {
         initialize_importer, PyInit_oxidized_importer, OXIDIZED_IMPORTER_NAME,
         OXIDIZED_IMPORTER_NAME_STR,
     },
-    super::osutils::resolve_terminfo_dirs,
+    super::interpreter_config::resolve_origin_path,
+    super::osutils::{resolve_system_terminfo_dirs, resolve_terminfo_dirs},
     super::pyalloc::{make_raw_rust_memory_allocator, RawAllocator},
+    super::python_eval::run,
     super::python_resources::PythonResourcesState,
     cpython::{
         GILGuard, NoArgs, ObjectProtocol, PyDict, PyErr, PyList, PyObject, PyString, Python,
@@ -32,6 +34,8 @@ use {
 
 #[cfg(feature = "jemalloc-sys")]
 use super::pyalloc::make_raw_jemalloc_allocator;
+#[cfg(feature = "libmimalloc-sys")]
+use super::pyalloc::make_raw_mimalloc_allocator;
 use python3_sys::PyMemAllocatorEx;
 
 lazy_static! {
@@ -48,6 +52,16 @@ fn raw_jemallocator() -> pyffi::PyMemAllocatorEx {
     panic!("jemalloc is not available in this build configuration");
 }
 
+#[cfg(feature = "libmimalloc-sys")]
+fn raw_mimallocator() -> pyffi::PyMemAllocatorEx {
+    make_raw_mimalloc_allocator()
+}
+
+#[cfg(not(feature = "libmimalloc-sys"))]
+fn raw_mimallocator() -> pyffi::PyMemAllocatorEx {
+    panic!("mimalloc is not available in this build configuration");
+}
+
 /// Format a PyErr in a crude manner.
 ///
 /// This is meant to be called during interpreter initialization. We can't
@@ -217,12 +231,26 @@ impl<'python, 'interpreter, 'resources> MainPythonInterpreter<'python, 'interpre
                     env::set_var("TERMINFO_DIRS", &v);
                 }
             }
+            TerminfoResolution::SystemPaths => {
+                if let Some(v) = resolve_system_terminfo_dirs() {
+                    env::set_var("TERMINFO_DIRS", &v);
+                }
+            }
             TerminfoResolution::Static(ref v) => {
                 env::set_var("TERMINFO_DIRS", v);
             }
             TerminfoResolution::None => {}
         }
 
+        if let (Some(path), Some(key)) = (
+            &config.write_modules_directory,
+            &config.write_modules_directory_env,
+        ) {
+            let path =
+                resolve_origin_path(path).or_else(|e| Err(NewInterpreterError::Dynamic(e)))?;
+            env::set_var(key, path);
+        }
+
         let mut res = MainPythonInterpreter {
             config,
             interpreter_guard: None,
@@ -306,6 +334,9 @@ impl<'python, 'interpreter, 'resources> MainPythonInterpreter<'python, 'interpre
                 MemoryAllocatorBackend::Jemalloc => {
                     self.raw_allocator = Some(InterpreterRawAllocator::from(raw_jemallocator()));
                 }
+                MemoryAllocatorBackend::Mimalloc => {
+                    self.raw_allocator = Some(InterpreterRawAllocator::from(raw_mimallocator()));
+                }
                 MemoryAllocatorBackend::Rust => {
                     self.raw_allocator = Some(InterpreterRawAllocator::from(
                         make_raw_rust_memory_allocator(),
@@ -566,6 +597,11 @@ impl<'python, 'interpreter, 'resources> MainPythonInterpreter<'python, 'interpre
     /// `PythonRunMode::Module`, and `PythonRunMode::Repl` run modes are
     /// evaluated via `Py_RunMain()`. `PythonRunMode::None` simply returns 0.
     ///
+    /// For `PythonRunMode::Sequence`, all steps but the last are evaluated
+    /// via the `python_eval` module before the last step is handled as
+    /// described above. If a step raises an exception, it is printed and 1 is
+    /// returned without evaluating subsequent steps.
+    ///
     /// `Py_RunMain` is the most robust mechanism to run code, files, or
     /// modules, as `Py_RunMain()` invokes the same APIs that `python` would.
     /// By contrast, the `run()`, `run_module_as_main()`, `run_code()`,
@@ -580,6 +616,31 @@ impl<'python, 'interpreter, 'resources> MainPythonInterpreter<'python, 'interpre
     /// to keep the interpreter alive or inspect the evaluation result, consider
     /// calling a function in the `python_eval` module.
     pub fn run_as_main(&mut self) -> i32 {
+        let setup_modes = self
+            .config
+            .run
+            .setup_modes()
+            .into_iter()
+            .cloned()
+            .collect::<Vec<_>>();
+
+        if !setup_modes.is_empty() {
+            let py = match self.acquire_gil() {
+                Ok(py) => py,
+                Err(msg) => {
+                    eprintln!("error acquiring GIL: {}", msg);
+                    return 1;
+                }
+            };
+
+            for mode in &setup_modes {
+                if let Err(err) = run(py, mode) {
+                    err.print(py);
+                    return 1;
+                }
+            }
+        }
+
         if self.config.uses_py_runmain() {
             let res = unsafe { pyffi::Py_RunMain() };
 
@@ -713,14 +774,19 @@ impl<'python, 'interpreter, 'resources> Drop
     for MainPythonInterpreter<'python, 'interpreter, 'resources>
 {
     fn drop(&mut self) {
-        if let Some(key) = &self.config.write_modules_directory_env {
-            if let Ok(path) = env::var(key) {
-                let path = PathBuf::from(path);
-                let py = self.acquire_gil().unwrap();
+        let path = if let Some(path) = &self.config.write_modules_directory {
+            resolve_origin_path(path).ok()
+        } else if let Some(key) = &self.config.write_modules_directory_env {
+            env::var(key).ok().map(PathBuf::from)
+        } else {
+            None
+        };
 
-                if let Err(msg) = write_modules_to_directory(py, &path) {
-                    eprintln!("error writing modules file: {}", msg);
-                }
+        if let Some(path) = path {
+            let py = self.acquire_gil().unwrap();
+
+            if let Err(msg) = write_modules_to_directory(py, &path) {
+                eprintln!("error writing modules file: {}", msg);
             }
         }
 
@@ -13,7 +13,7 @@ use {
     python3_sys as pyffi,
     std::convert::TryInto,
     std::ffi::{CStr, CString, OsStr},
-    std::path::Path,
+    std::path::{Path, PathBuf},
 };
 
 #[cfg(unix)]
@@ -22,6 +22,27 @@ use std::os::unix::ffi::OsStrExt;
 #[cfg(target_family = "windows")]
 use std::os::windows::prelude::OsStrExt;
 
+/// Resolve a leading `$ORIGIN` path component to the directory of the current executable.
+///
+/// `$APPDIR` is accepted as an alias for `$ORIGIN`. Paths not starting with
+/// either token are returned as-is.
+pub(crate) fn resolve_origin_path(path: &Path) -> Result<PathBuf, String> {
+    if let Ok(rest) = path
+        .strip_prefix("$ORIGIN")
+        .or_else(|_| path.strip_prefix("$APPDIR"))
+    {
+        let exe = std::env::current_exe()
+            .or_else(|err| Err(format!("unable to obtain current executable: {}", err)))?;
+        let origin = exe
+            .parent()
+            .ok_or_else(|| "unable to get current executable directory".to_string())?;
+
+        Ok(origin.join(rest))
+    } else {
+        Ok(path.to_path_buf())
+    }
+}
+
 fn py_status_to_string(status: &pyffi::PyStatus, context: &str) -> String {
     if !status.func.is_null() && !status.err_msg.is_null() {
         let func = unsafe { CStr::from_ptr(status.func) };
@@ -235,12 +256,13 @@ impl<'a> OxidizedPythonInterpreterConfig<'a> {
         {
             true
         } else {
-            match &self.run {
+            match self.run.main_mode() {
                 PythonRunMode::Eval { .. } => true,
                 PythonRunMode::File { .. } => true,
                 PythonRunMode::Module { .. } => true,
                 PythonRunMode::Repl => true,
                 PythonRunMode::None => false,
+                PythonRunMode::Sequence { .. } => false,
             }
         }
     }
@@ -491,7 +513,7 @@ impl TryInto<pyffi::PyConfig> for &PythonInterpreterConfig {
             for path in module_search_paths {
                 append_wide_string_list_from_path(
                     &mut config.module_search_paths,
-                    path,
+                    &resolve_origin_path(path)?,
                     "setting module_search_paths",
                 )?;
             }
@@ -612,9 +634,10 @@ impl<'a> TryInto<pyffi::PyConfig> for &'a OxidizedPythonInterpreterConfig<'a> {
             }
         }
 
-        match &self.run {
+        match self.run.main_mode() {
             PythonRunMode::None => {}
             PythonRunMode::Repl => {}
+            PythonRunMode::Sequence { .. } => {}
             PythonRunMode::Eval { code } => {
                 if self.interpreter_config.run_command.is_none() {
                     set_config_string_from_str(
@@ -630,7 +653,7 @@ impl<'a> TryInto<pyffi::PyConfig> for &'a OxidizedPythonInterpreterConfig<'a> {
                     set_config_string_from_path(
                         &config,
                         &config.run_filename,
-                        path,
+                        &resolve_origin_path(path)?,
                         "setting run_filename",
                     )?;
                 }
@@ -42,7 +42,7 @@ At this time, we have required direct dependencies on published versions of the
 `anyhow`, `lazy_static`, `libc`, `memmap`, `python-packed-resources`, and `uuid`
 crates. On Windows, this list is extended by `memory-module-sys` and `winapi`,
 which are required to support loading DLLs from memory. We also have an optional
-direct dependency on the `jemalloc-sys` crate.
+direct dependency on the `jemalloc-sys` and `libmimalloc-sys` crates.
 
 This crate requires linking against a library providing CPython C symbols.
 (This dependency is via the `python3-sys` crate.) On Windows, this library
@@ -56,6 +56,10 @@ from Python is a run-time configuration option controlled by the
 `PythonConfig` type and having `jemalloc` compiled into the binary does not
 mean it is being used!
 
+Similarly, the optional `mimalloc` feature controls support for using
+[mimalloc](https://github.com/microsoft/mimalloc) as Python's memory
+allocator. Unlike jemalloc, mimalloc works on Windows.
+
 There exist mutually exclusive `build-mode-*` features to control how the
 `build.rs` build script works.
 
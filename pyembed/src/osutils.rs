@@ -101,6 +101,35 @@ fn resolve_os_info() -> OsInfo {
     OsInfo { os, linux_distro }
 }
 
+/// Obtain a `TERMINFO_DIRS` value consisting of well-known terminfo directories that exist.
+fn probe_common_terminfo_dirs() -> String {
+    TERMINFO_DIRS_COMMON
+        .iter()
+        .filter_map(|p| {
+            if p.exists() {
+                Some(p.display().to_string())
+            } else {
+                None
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(":")
+}
+
+/// Resolve `TERMINFO_DIRS` by probing well-known terminfo directories.
+///
+/// Unlike `resolve_terminfo_dirs()`, this doesn't attempt to identify the
+/// running operating system and always looks for existing directories.
+///
+/// Returns None if `TERMINFO_DIRS` is already set or on Windows.
+pub fn resolve_system_terminfo_dirs() -> Option<String> {
+    if std::env::var("TERMINFO_DIRS").is_ok() || cfg!(windows) {
+        None
+    } else {
+        Some(probe_common_terminfo_dirs())
+    }
+}
+
 /// Attempt to resolve the value for the `TERMINFO_DIRS` environment variable.
 ///
 /// Returns Some() value that `TERMINFO_DIRS` should be set to or None if
@@ -124,19 +153,7 @@ pub fn resolve_terminfo_dirs() -> Option<String> {
             LinuxDistroVariant::Unknown => {
                 // We don't know this Linux variant. Look for common terminfo
                 // database directories and use paths that are found.
-                let paths = TERMINFO_DIRS_COMMON
-                    .iter()
-                    .filter_map(|p| {
-                        if p.exists() {
-                            Some(p.display().to_string())
-                        } else {
-                            None
-                        }
-                    })
-                    .collect::<Vec<String>>()
-                    .join(":");
-
-                Some(paths)
+                Some(probe_common_terminfo_dirs())
             }
         },
         OsVariant::MacOs => Some(TERMINFO_DIRS_MACOS.to_string()),
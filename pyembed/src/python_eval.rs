@@ -7,6 +7,7 @@
 use {
     super::config::PythonRunMode,
     super::conversion::path_to_cstring,
+    super::interpreter_config::resolve_origin_path,
     cpython::exc::{RuntimeError, SystemExit, ValueError},
     cpython::{
         NoArgs, ObjectProtocol, PyClone, PyErr, PyModule, PyObject, PyResult, Python, PythonObject,
@@ -249,6 +250,9 @@ pub fn run_repl(py: Python) -> PyResult<PyObject> {
 /// etc would do things. If you would like exact conformance with these
 /// run modes, use `OxidizedPythonInterpreterConfig.run_as_main()` instead,
 /// as that will evaluate using a Python API that does what `python` would do.
+///
+/// A leading `$ORIGIN` component in `PythonRunMode::File` paths is resolved
+/// to the directory of the current executable.
 pub fn run(py: Python, run_mode: &PythonRunMode) -> PyResult<PyObject> {
     // Clone here because we call into &mut self functions and can't have
     // an immutable reference to &self.
@@ -257,7 +261,21 @@ pub fn run(py: Python, run_mode: &PythonRunMode) -> PyResult<PyObject> {
         PythonRunMode::Repl => run_repl(py),
         PythonRunMode::Module { module } => run_module_as_main(py, module),
         PythonRunMode::Eval { code } => run_code(py, code),
-        PythonRunMode::File { path } => run_file(py, path),
+        PythonRunMode::File { path } => {
+            let path =
+                resolve_origin_path(path).or_else(|e| Err(PyErr::new::<RuntimeError, _>(py, e)))?;
+
+            run_file(py, &path)
+        }
+        PythonRunMode::Sequence { steps } => {
+            let mut res = py.None();
+
+            for step in steps {
+                res = run(py, step)?;
+            }
+
+            Ok(res)
+        }
     }
 }
 
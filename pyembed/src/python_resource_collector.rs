@@ -13,133 +13,242 @@ use {
     crate::python_resources::resource_to_pyobject,
     cpython::exc::{TypeError, ValueError},
     cpython::{
-        py_class, py_class_prop_getter, ObjectProtocol, PyBytes, PyErr, PyObject, PyResult, Python,
-        PythonObject, ToPyObject,
+        py_class, py_class_prop_getter, NoArgs, ObjectProtocol, PyBytes, PyDict, PyErr, PyObject,
+        PyResult, Python, PythonObject, ToPyObject,
+    },
+    python_packaging::resource::{
+        DataLocation, PythonPackageDistributionResource as RawPythonPackageDistributionResource,
+        PythonPackageDistributionResourceFlavor,
     },
     python_packaging::resource_collection::{
         PreparedPythonResources, PythonResourceCollector, PythonResourcesPolicy,
     },
     std::cell::RefCell,
+    std::collections::HashMap,
     std::convert::TryFrom,
+    std::path::Path,
 };
 
 py_class!(pub class OxidizedResourceCollector |py| {
     data collector: RefCell<PythonResourceCollector>;
 
-    def __new__(_cls, policy: String) -> PyResult<OxidizedResourceCollector> {
-        OxidizedResourceCollector::new(py, policy)
+    def __new__(_cls, policy: String, cache_tag: Option<String> = None) -> PyResult<OxidizedResourceCollector> {
+        OxidizedResourceCollector::new(py, policy, cache_tag)
     }
 
     def __repr__(&self) -> PyResult<String> {
         Ok("<OxidizedResourceCollector>".to_string())
     }
 
+    def __len__(&self) -> PyResult<usize> {
+        Ok(self.collector(py).borrow().len())
+    }
+
+    def __contains__(&self, name: String) -> PyResult<bool> {
+        Ok(self.collector(py).borrow().has_resource(&name))
+    }
+
     @property def policy(&self) -> PyResult<String> {
         Ok(self.collector(py).borrow().get_policy().into())
     }
 
-    def add_in_memory(&self, resource: PyObject) -> PyResult<PyObject> {
-        self.add_in_memory_impl(py, resource)
+    @property def policy_details(&self) -> PyResult<PyObject> {
+        self.policy_details_impl(py)
+    }
+
+    def add_in_memory(&self, resource: PyObject, expected_sha256: Option<String> = None) -> PyResult<PyObject> {
+        self.add_in_memory_impl(py, resource, expected_sha256)
+    }
+
+    def add_in_memory_many(&self, resources: PyObject) -> PyResult<PyObject> {
+        self.add_in_memory_many_impl(py, resources)
+    }
+
+    def filter_by_policy(&self, resources: PyObject) -> PyResult<PyObject> {
+        self.filter_by_policy_impl(py, resources)
     }
 
     def add_filesystem_relative(&self, prefix: String, resource: PyObject) -> PyResult<PyObject> {
         self.add_filesystem_relative_impl(py, prefix, resource)
     }
 
+    def add_direct_url(&self, package: String, url: String, vcs_info: Option<PyObject> = None) -> PyResult<PyObject> {
+        self.add_direct_url_impl(py, package, url, vcs_info)
+    }
+
+    def remove(&self, name: String) -> PyResult<bool> {
+        Ok(self.collector(py).borrow_mut().remove_resource(&name))
+    }
+
+    def validate(&self) -> PyResult<Vec<String>> {
+        Ok(self.collector(py).borrow().find_orphaned_package_resources())
+    }
+
+    def equals(&self, other: OxidizedResourceCollector) -> PyResult<bool> {
+        let other = other.collector(py).borrow();
+
+        self.collector(py)
+            .borrow()
+            .has_same_resources(&other)
+            .or_else(|e| Err(PyErr::new::<ValueError, _>(py, e.to_string())))
+    }
+
+    def merge(&self, other: OxidizedResourceCollector, overwrite: bool = false) -> PyResult<PyObject> {
+        // Copy first so merging an instance into itself doesn't double borrow.
+        let other = other.collector(py).borrow().clone();
+
+        self.collector(py)
+            .borrow_mut()
+            .merge(&other, overwrite)
+            .or_else(|e| Err(PyErr::new::<ValueError, _>(py, e.to_string())))?;
+
+        Ok(py.None())
+    }
+
+    def snapshot(&self) -> PyResult<PyObject> {
+        self.snapshot_impl(py)
+    }
+
+    def restore(&self, token: PyObject) -> PyResult<PyObject> {
+        self.restore_impl(py, token)
+    }
+
     def oxidize(&self) -> PyResult<PyObject> {
         self.oxidize_impl(py)
     }
+
+    def to_frozen_c_array(&self, variable_name: String) -> PyResult<String> {
+        self.to_frozen_c_array_impl(py, variable_name)
+    }
+
+    def to_cas(&self) -> PyResult<PyObject> {
+        self.to_cas_impl(py)
+    }
 });
 
-impl OxidizedResourceCollector {
-    pub fn new(py: Python, policy: String) -> PyResult<Self> {
-        let policy = PythonResourcesPolicy::try_from(policy.as_ref())
-            .or_else(|e| Err(PyErr::new::<ValueError, _>(py, e.to_string())))?;
+// Opaque token holding a copy of collector state.
+py_class!(pub class OxidizedResourceCollectorSnapshot |py| {
+    data collector: PythonResourceCollector;
 
-        let sys_module = py.import("sys")?;
-        let cache_tag = sys_module
-            .get(py, "implementation")?
-            .getattr(py, "cache_tag")?
-            .extract::<String>(py)?;
+    def __repr__(&self) -> PyResult<String> {
+        Ok("<OxidizedResourceCollectorSnapshot>".to_string())
+    }
+});
+
+impl OxidizedResourceCollector {
+    pub fn new(py: Python, policy: String, cache_tag: Option<String>) -> PyResult<Self> {
+        let policy = PythonResourcesPolicy::try_from(policy.as_ref()).or_else(|e| {
+            Err(PyErr::new::<ValueError, _>(
+                py,
+                format!(
+                    "{}; valid policies are in-memory-only, \
+                     filesystem-relative-only[:<prefix>], and \
+                     prefer-in-memory-fallback-filesystem-relative[:<prefix>]",
+                    e
+                ),
+            ))
+        })?;
+
+        let cache_tag = match cache_tag {
+            Some(cache_tag) => cache_tag,
+            None => {
+                let sys_module = py.import("sys")?;
+                sys_module
+                    .get(py, "implementation")?
+                    .getattr(py, "cache_tag")?
+                    .extract::<String>(py)?
+            }
+        };
 
         let collector = PythonResourceCollector::new(&policy, &cache_tag);
 
         OxidizedResourceCollector::create_instance(py, RefCell::new(collector))
     }
 
-    fn add_in_memory_impl(&self, py: Python, resource: PyObject) -> PyResult<PyObject> {
+    fn add_in_memory_impl(
+        &self,
+        py: Python,
+        resource: PyObject,
+        expected_sha256: Option<String>,
+    ) -> PyResult<PyObject> {
+        if let Some(expected) = expected_sha256 {
+            verify_resource_sha256(py, &resource, &expected)?;
+        }
+
         let mut collector = self.collector(py).borrow_mut();
-        let typ = resource.get_type(py);
 
-        match typ.name(py).as_ref() {
-            "PythonExtensionModule" => {
-                let module = resource.cast_into::<PythonExtensionModule>(py)?;
+        add_in_memory_resource(py, &mut collector, resource)?;
 
-                let resource = module.get_resource(py);
+        Ok(py.None())
+    }
 
-                if let Some(location) = &resource.extension_data {
-                    let data = location.resolve().or_else(|e| {
-                        Err(PyErr::new::<ValueError, _>(
-                            py,
-                            "unable to resolve extension data",
-                        ))
-                    })?;
+    fn add_in_memory_many_impl(&self, py: Python, resources: PyObject) -> PyResult<PyObject> {
+        // Consume the iterable before borrowing the collector, as iterating
+        // can run arbitrary Python code that accesses this collector.
+        let resources = resources.iter(py)?.collect::<PyResult<Vec<PyObject>>>()?;
 
-                    collector
-                        .add_in_memory_python_extension_module_shared_library(
-                            &resource.name,
-                            resource.is_package,
-                            &data,
-                            // TODO handle shared libraries.
-                            &[],
-                        )
-                        .or_else(|e| Err(PyErr::new::<ValueError, _>(py, e.to_string())))?;
-
-                    Ok(py.None())
-                } else {
-                    Err(PyErr::new::<ValueError, _>(
-                        py,
-                        "PythonExtensionModule lacks a shared library",
-                    ))
-                }
-            }
-            "PythonModuleBytecode" => {
-                let module = resource.cast_into::<PythonModuleBytecode>(py)?;
-                collector
-                    .add_in_memory_python_module_bytecode(&module.get_resource(py))
-                    .or_else(|e| Err(PyErr::new::<ValueError, _>(py, e.to_string())))?;
+        let mut collector = self.collector(py).borrow_mut();
+        let mut errors = Vec::new();
 
-                Ok(py.None())
-            }
-            "PythonModuleSource" => {
-                let module = resource.cast_into::<PythonModuleSource>(py)?;
-                collector
-                    .add_in_memory_python_module_source(&module.get_resource(py))
-                    .or_else(|e| Err(PyErr::new::<ValueError, _>(py, e.to_string())))?;
+        for resource in resources {
+            let repr = resource.repr(py)?.to_string_lossy(py).to_string();
 
-                Ok(py.None())
+            if let Err(mut err) = add_in_memory_resource(py, &mut collector, resource) {
+                let message = err.instance(py).str(py)?.to_string_lossy(py).to_string();
+                errors.push(format!("{}: {}", repr, message));
             }
-            "PythonPackageResource" => {
-                let resource = resource.cast_into::<PythonPackageResource>(py)?;
-                collector
-                    .add_in_memory_python_package_resource(&resource.get_resource(py))
-                    .or_else(|e| Err(PyErr::new::<ValueError, _>(py, e.to_string())))?;
+        }
 
-                Ok(py.None())
+        if errors.is_empty() {
+            Ok(py.None())
+        } else {
+            Err(PyErr::new::<ValueError, _>(
+                py,
+                format!(
+                    "error adding {} resources:\n{}",
+                    errors.len(),
+                    errors.join("\n")
+                ),
+            ))
+        }
+    }
+
+    fn policy_details_impl(&self, py: Python) -> PyResult<PyObject> {
+        let (in_memory, relative_prefix) = match self.collector(py).borrow().get_policy() {
+            PythonResourcesPolicy::InMemoryOnly => (true, None),
+            PythonResourcesPolicy::FilesystemRelativeOnly(prefix) => (false, Some(prefix.clone())),
+            PythonResourcesPolicy::PreferInMemoryFallbackFilesystemRelative(prefix) => {
+                (true, Some(prefix.clone()))
             }
-            "PythonPackageDistributionResource" => {
-                let resource = resource.cast_into::<PythonPackageDistributionResource>(py)?;
-                collector
-                    .add_in_memory_package_distribution_resource(&resource.get_resource(py))
-                    .or_else(|e| Err(PyErr::new::<ValueError, _>(py, e.to_string())))?;
+        };
 
-                Ok(py.None())
+        let details = PyDict::new(py);
+        details.set_item(py, "in_memory", in_memory)?;
+        details.set_item(py, "filesystem_relative", relative_prefix.is_some())?;
+        details.set_item(py, "filesystem_relative_prefix", relative_prefix)?;
+
+        Ok(details.into_object())
+    }
+
+    fn filter_by_policy_impl(&self, py: Python, resources: PyObject) -> PyResult<PyObject> {
+        // Resources are added to a copy of the collector so checks that depend on
+        // already collected state behave as they would for add_in_memory().
+        let mut trial = self.collector(py).borrow().clone();
+
+        let mut accepted = Vec::new();
+        let mut rejected = Vec::new();
+
+        for resource in resources.iter(py)? {
+            let resource = resource?;
+
+            if add_in_memory_resource(py, &mut trial, resource.clone_ref(py)).is_ok() {
+                accepted.push(resource);
+            } else {
+                rejected.push(resource);
             }
-            _ => Err(PyErr::new::<TypeError, _>(
-                py,
-                format!("cannot operate on {} values", typ.name(py)),
-            )),
         }
+
+        Ok((accepted, rejected).to_py_object(py).into_object())
     }
 
     fn add_filesystem_relative_impl(
@@ -148,6 +257,13 @@ impl OxidizedResourceCollector {
         prefix: String,
         resource: PyObject,
     ) -> PyResult<PyObject> {
+        if std::path::Path::new(&prefix).is_absolute() {
+            return Err(PyErr::new::<ValueError, _>(
+                py,
+                format!("prefix must be a relative path: got {}", prefix),
+            ));
+        }
+
         let mut collector = self.collector(py).borrow_mut();
 
         match resource.get_type(py).name(py).as_ref() {
@@ -179,9 +295,19 @@ impl OxidizedResourceCollector {
             }
             "PythonPackageResource" => {
                 let resource = resource.cast_into::<PythonPackageResource>(py)?;
+                let resource = resource.get_resource(py);
+
                 collector
-                    .add_relative_path_python_package_resource(&prefix, &resource.get_resource(py))
-                    .or_else(|e| Err(PyErr::new::<ValueError, _>(py, e.to_string())))?;
+                    .add_relative_path_python_package_resource(&prefix, &resource)
+                    .or_else(|e| {
+                        Err(PyErr::new::<ValueError, _>(
+                            py,
+                            format!(
+                                "error adding resource {} in package {}: {}",
+                                resource.relative_name, resource.leaf_package, e
+                            ),
+                        ))
+                    })?;
 
                 Ok(py.None())
             }
@@ -203,22 +329,106 @@ impl OxidizedResourceCollector {
         }
     }
 
-    fn oxidize_impl(&self, py: Python) -> PyResult<PyObject> {
+    fn add_direct_url_impl(
+        &self,
+        py: Python,
+        package: String,
+        url: String,
+        vcs_info: Option<PyObject>,
+    ) -> PyResult<PyObject> {
+        // See PEP 610 for the format of this file.
+        let info = PyDict::new(py);
+        info.set_item(py, "url", &url)?;
+
+        match vcs_info {
+            Some(vcs_info) => info.set_item(py, "vcs_info", vcs_info)?,
+            None if file_url_is_dir(py, &url)? => info.set_item(py, "dir_info", PyDict::new(py))?,
+            None => info.set_item(py, "archive_info", PyDict::new(py))?,
+        }
+
+        let json = py.import("json")?;
+        let data = json
+            .call(py, "dumps", (info,), None)?
+            .extract::<String>(py)?;
+
+        let resource = RawPythonPackageDistributionResource {
+            location: PythonPackageDistributionResourceFlavor::DistInfo,
+            package,
+            // In-memory distribution resources are indexed by package name, so
+            // the version is never consulted.
+            version: String::new(),
+            name: "direct_url.json".to_string(),
+            data: DataLocation::Memory(data.into_bytes()),
+        };
+
+        self.collector(py)
+            .borrow_mut()
+            .add_in_memory_package_distribution_resource(&resource)
+            .or_else(|e| Err(PyErr::new::<ValueError, _>(py, e.to_string())))?;
+
+        Ok(py.None())
+    }
+
+    fn snapshot_impl(&self, py: Python) -> PyResult<PyObject> {
+        let collector = self.collector(py).borrow().clone();
+
+        Ok(OxidizedResourceCollectorSnapshot::create_instance(py, collector)?.into_object())
+    }
+
+    fn restore_impl(&self, py: Python, token: PyObject) -> PyResult<PyObject> {
+        let snapshot = token
+            .cast_into::<OxidizedResourceCollectorSnapshot>(py)
+            .or_else(|_| {
+                Err(PyErr::new::<TypeError, _>(
+                    py,
+                    "restore() requires a value returned by snapshot()",
+                ))
+            })?;
+
+        self.collector(py).replace(snapshot.collector(py).clone());
+
+        Ok(py.None())
+    }
+
+    /// Prepare collected resources, compiling bytecode with the running interpreter.
+    fn prepare_resources(&self, py: Python) -> PyResult<PreparedPythonResources> {
         let sys_module = py.import("sys")?;
         let executable = sys_module.get(py, "executable")?;
+        let cache_tag = sys_module
+            .get(py, "implementation")?
+            .getattr(py, "cache_tag")?
+            .extract::<String>(py)?;
 
         let python_exe = pyobject_to_pathbuf(py, executable)?;
 
         let collector = self.collector(py).borrow();
 
-        let prepared: PreparedPythonResources = collector
+        let error =
+            |e: anyhow::Error| PyErr::new::<ValueError, _>(py, format!("error oxidizing: {}", e));
+
+        // Bytecode is compiled with the running interpreter, so it is only
+        // valid for collectors targeting the running interpreter's version.
+        if collector.get_cache_tag() != cache_tag
+            && collector.requires_bytecode_compilation().map_err(error)?
+        {
+            return Err(PyErr::new::<ValueError, _>(
+                py,
+                format!(
+                    "error oxidizing: cannot compile bytecode for cache tag {} \
+                     with a Python interpreter using cache tag {}",
+                    collector.get_cache_tag(),
+                    cache_tag
+                ),
+            ));
+        }
+
+        collector
             .to_prepared_python_resources(&python_exe)
-            .or_else(|e| {
-                Err(PyErr::new::<ValueError, _>(
-                    py,
-                    format!("error oxidizing: {}", e),
-                ))
-            })?;
+            .map_err(error)
+    }
+
+    fn oxidize_impl(&self, py: Python) -> PyResult<PyObject> {
+        let prepared = self.prepare_resources(py)?;
 
         let mut resources = Vec::new();
 
@@ -243,4 +453,351 @@ impl OxidizedResourceCollector {
             .into_py_object(py)
             .into_object())
     }
+
+    fn to_frozen_c_array_impl(&self, py: Python, variable_name: String) -> PyResult<String> {
+        let prepared = self.prepare_resources(py)?;
+
+        prepared
+            .to_frozen_c_array(&variable_name)
+            .or_else(|e| Err(PyErr::new::<ValueError, _>(py, e.to_string())))
+    }
+
+    fn to_cas_impl(&self, py: Python) -> PyResult<PyObject> {
+        let prepared = self.prepare_resources(py)?;
+
+        let blobs = PyDict::new(py);
+        let manifest = PyDict::new(py);
+
+        let extra_files = prepared
+            .extra_files
+            .iter()
+            .map(|(path, location, _)| (path.as_path(), location))
+            .collect::<HashMap<&Path, &DataLocation>>();
+
+        for (name, resource) in &prepared.resources {
+            let entry = PyDict::new(py);
+
+            for (field, data) in &[
+                ("in_memory_source", &resource.in_memory_source),
+                ("in_memory_bytecode", &resource.in_memory_bytecode),
+                ("in_memory_bytecode_opt1", &resource.in_memory_bytecode_opt1),
+                ("in_memory_bytecode_opt2", &resource.in_memory_bytecode_opt2),
+                (
+                    "in_memory_extension_module_shared_library",
+                    &resource.in_memory_extension_module_shared_library,
+                ),
+                (
+                    "in_memory_shared_library",
+                    &resource.in_memory_shared_library,
+                ),
+            ] {
+                if let Some(data) = data {
+                    entry.set_item(py, *field, add_cas_blob(py, &blobs, data)?)?;
+                }
+            }
+
+            for (field, resources) in &[
+                (
+                    "in_memory_package_resources",
+                    &resource.in_memory_package_resources,
+                ),
+                (
+                    "in_memory_distribution_resources",
+                    &resource.in_memory_distribution_resources,
+                ),
+            ] {
+                if let Some(resources) = resources {
+                    let digests = PyDict::new(py);
+
+                    for (key, data) in resources.iter() {
+                        digests.set_item(py, key, add_cas_blob(py, &blobs, data)?)?;
+                    }
+
+                    entry.set_item(py, *field, digests)?;
+                }
+            }
+
+            for (field, path) in &[
+                (
+                    "relative_path_module_source",
+                    &resource.relative_path_module_source,
+                ),
+                (
+                    "relative_path_module_bytecode",
+                    &resource.relative_path_module_bytecode,
+                ),
+                (
+                    "relative_path_module_bytecode_opt1",
+                    &resource.relative_path_module_bytecode_opt1,
+                ),
+                (
+                    "relative_path_module_bytecode_opt2",
+                    &resource.relative_path_module_bytecode_opt2,
+                ),
+                (
+                    "relative_path_extension_module_shared_library",
+                    &resource.relative_path_extension_module_shared_library,
+                ),
+            ] {
+                if let Some(path) = path {
+                    let data = extra_file_data(py, &extra_files, path)?;
+                    entry.set_item(py, *field, add_cas_blob(py, &blobs, &data)?)?;
+                }
+            }
+
+            for (field, resources) in &[
+                (
+                    "relative_path_package_resources",
+                    &resource.relative_path_package_resources,
+                ),
+                (
+                    "relative_path_distribution_resources",
+                    &resource.relative_path_distribution_resources,
+                ),
+            ] {
+                if let Some(resources) = resources {
+                    let digests = PyDict::new(py);
+
+                    for (key, path) in resources.iter() {
+                        let data = extra_file_data(py, &extra_files, path)?;
+                        digests.set_item(py, key, add_cas_blob(py, &blobs, &data)?)?;
+                    }
+
+                    entry.set_item(py, *field, digests)?;
+                }
+            }
+
+            if entry.len(py) > 0 {
+                manifest.set_item(py, name, entry)?;
+            }
+        }
+
+        Ok((blobs, manifest).into_py_object(py).into_object())
+    }
+}
+
+/// Compute the hex encoded SHA-256 digest of data.
+fn sha256_hex(py: Python, data: &[u8]) -> PyResult<String> {
+    py.import("hashlib")?
+        .call(py, "sha256", (PyBytes::new(py, data),), None)?
+        .call_method(py, "hexdigest", NoArgs, None)?
+        .extract::<String>(py)
+}
+
+/// Store data in a content-addressed blobs dict, returning its key.
+///
+/// Blobs are keyed by the hex encoded SHA-256 of their content, so identical
+/// data is only stored once.
+fn add_cas_blob(py: Python, blobs: &PyDict, data: &[u8]) -> PyResult<String> {
+    let digest = sha256_hex(py, data)?;
+
+    if blobs.get_item(py, &digest).is_none() {
+        blobs.set_item(py, &digest, PyBytes::new(py, data))?;
+    }
+
+    Ok(digest)
+}
+
+/// Whether a URL is a `file://` URL referring to a local directory.
+fn file_url_is_dir(py: Python, url: &str) -> PyResult<bool> {
+    if !url.starts_with("file://") {
+        return Ok(false);
+    }
+
+    let path = py
+        .import("urllib.parse")?
+        .call(py, "urlparse", (url,), None)?
+        .getattr(py, "path")?;
+    let path = py
+        .import("urllib.request")?
+        .call(py, "url2pathname", (path,), None)?;
+
+    py.import("os.path")?
+        .call(py, "isdir", (path,), None)?
+        .extract::<bool>(py)
+}
+
+/// Resolve the data of a file installed relative to the packaged binary.
+///
+/// `ValueError` is raised if no file is installed at `path`.
+fn extra_file_data(
+    py: Python,
+    extra_files: &HashMap<&Path, &DataLocation>,
+    path: &Path,
+) -> PyResult<Vec<u8>> {
+    extra_files
+        .get(path)
+        .ok_or_else(|| {
+            PyErr::new::<ValueError, _>(py, format!("no file installed at {}", path.display()))
+        })?
+        .resolve()
+        .or_else(|e| Err(PyErr::new::<ValueError, _>(py, e.to_string())))
+}
+
+/// Resolve the data held by a Python resource type.
+///
+/// This is module source, bytecode, or extension module data, or the content of
+/// a package resource, depending on the resource type.
+fn resource_content(py: Python, resource: &PyObject) -> PyResult<Vec<u8>> {
+    let typ = resource.get_type(py);
+    let resource = resource.clone_ref(py);
+
+    let data = match typ.name(py).as_ref() {
+        "PythonExtensionModule" => {
+            let module = resource.cast_into::<PythonExtensionModule>(py)?;
+            let module = module.get_resource(py);
+
+            match &module.extension_data {
+                Some(location) => location.resolve(),
+                None => {
+                    return Err(PyErr::new::<ValueError, _>(
+                        py,
+                        "PythonExtensionModule lacks a shared library",
+                    ));
+                }
+            }
+        }
+        "PythonModuleBytecode" => {
+            let module = resource.cast_into::<PythonModuleBytecode>(py)?;
+            let module = module.get_resource(py);
+
+            module.resolve_bytecode()
+        }
+        "PythonModuleSource" => {
+            let module = resource.cast_into::<PythonModuleSource>(py)?;
+            let module = module.get_resource(py);
+
+            module.source.resolve()
+        }
+        "PythonPackageResource" => {
+            let resource = resource.cast_into::<PythonPackageResource>(py)?;
+            let resource = resource.get_resource(py);
+
+            resource.data.resolve()
+        }
+        "PythonPackageDistributionResource" => {
+            let resource = resource.cast_into::<PythonPackageDistributionResource>(py)?;
+            let resource = resource.get_resource(py);
+
+            resource.data.resolve()
+        }
+        _ => {
+            return Err(PyErr::new::<TypeError, _>(
+                py,
+                format!("cannot operate on {} values", typ.name(py)),
+            ));
+        }
+    };
+
+    data.or_else(|e| Err(PyErr::new::<ValueError, _>(py, e.to_string())))
+}
+
+/// Verify the data of a Python resource type has an expected SHA-256 digest.
+///
+/// `ValueError` is raised if the digest of the resource's data doesn't match.
+fn verify_resource_sha256(py: Python, resource: &PyObject, expected: &str) -> PyResult<()> {
+    let digest = sha256_hex(py, &resource_content(py, resource)?)?;
+
+    if digest == expected.to_lowercase() {
+        Ok(())
+    } else {
+        Err(PyErr::new::<ValueError, _>(
+            py,
+            format!(
+                "sha256 mismatch for {}: expected {}; got {}",
+                resource.repr(py)?.to_string_lossy(py),
+                expected,
+                digest
+            ),
+        ))
+    }
+}
+
+/// Add a Python resource type to a collector for in-memory loading.
+fn add_in_memory_resource(
+    py: Python,
+    collector: &mut PythonResourceCollector,
+    resource: PyObject,
+) -> PyResult<()> {
+    let typ = resource.get_type(py);
+
+    match typ.name(py).as_ref() {
+        "PythonExtensionModule" => {
+            let module = resource.cast_into::<PythonExtensionModule>(py)?;
+
+            let resource = module.get_resource(py);
+
+            if let Some(location) = &resource.extension_data {
+                let data = location.resolve().or_else(|e| {
+                    Err(PyErr::new::<ValueError, _>(
+                        py,
+                        "unable to resolve extension data",
+                    ))
+                })?;
+
+                collector
+                    .add_in_memory_python_extension_module_shared_library(
+                        &resource.name,
+                        resource.is_package,
+                        &data,
+                        // TODO handle shared libraries.
+                        &[],
+                    )
+                    .or_else(|e| Err(PyErr::new::<ValueError, _>(py, e.to_string())))?;
+
+                Ok(())
+            } else {
+                Err(PyErr::new::<ValueError, _>(
+                    py,
+                    "PythonExtensionModule lacks a shared library",
+                ))
+            }
+        }
+        "PythonModuleBytecode" => {
+            let module = resource.cast_into::<PythonModuleBytecode>(py)?;
+            collector
+                .add_in_memory_python_module_bytecode(&module.get_resource(py))
+                .or_else(|e| Err(PyErr::new::<ValueError, _>(py, e.to_string())))?;
+
+            Ok(())
+        }
+        "PythonModuleSource" => {
+            let module = resource.cast_into::<PythonModuleSource>(py)?;
+            collector
+                .add_in_memory_python_module_source(&module.get_resource(py))
+                .or_else(|e| Err(PyErr::new::<ValueError, _>(py, e.to_string())))?;
+
+            Ok(())
+        }
+        "PythonPackageResource" => {
+            let resource = resource.cast_into::<PythonPackageResource>(py)?;
+            let resource = resource.get_resource(py);
+
+            collector
+                .add_in_memory_python_package_resource(&resource)
+                .or_else(|e| {
+                    Err(PyErr::new::<ValueError, _>(
+                        py,
+                        format!(
+                            "error adding resource {} in package {}: {}",
+                            resource.relative_name, resource.leaf_package, e
+                        ),
+                    ))
+                })?;
+
+            Ok(())
+        }
+        "PythonPackageDistributionResource" => {
+            let resource = resource.cast_into::<PythonPackageDistributionResource>(py)?;
+            collector
+                .add_in_memory_package_distribution_resource(&resource.get_resource(py))
+                .or_else(|e| Err(PyErr::new::<ValueError, _>(py, e.to_string())))?;
+
+            Ok(())
+        }
+        _ => Err(PyErr::new::<TypeError, _>(
+            py,
+            format!("cannot operate on {} values", typ.name(py)),
+        )),
+    }
 }
@@ -136,7 +136,10 @@ py_class!(pub class PythonModuleBytecode |py| {
     @optimize_level.setter def set_optimize_level(&self, value: Option<i32>) -> PyResult<()> {
         if let Some(value) = value {
             let value = BytecodeOptimizationLevel::try_from(value).or_else(|_| {
-                Err(PyErr::new::<ValueError, _>(py, "invalid bytecode optimization level"))
+                Err(PyErr::new::<ValueError, _>(
+                    py,
+                    format!("optimize_level must be 0, 1, or 2: got {}", value),
+                ))
             })?;
 
             self.resource(py).borrow_mut().optimize_level = value;
@@ -3,9 +3,14 @@
 // file, You can obtain one at https://mozilla.org/MPL/2.0/.
 
 use {
-    crate::{MainPythonInterpreter, OxidizedPythonInterpreterConfig, PythonInterpreterProfile},
+    crate::interpreter_config::resolve_origin_path,
+    crate::{
+        CoerceCLocale, MainPythonInterpreter, OxidizedPythonInterpreterConfig, PythonConfig,
+        PythonInterpreterProfile, PythonRunMode,
+    },
     anyhow::Result,
     cpython::ObjectProtocol,
+    std::path::PathBuf,
 };
 
 #[test]
@@ -56,3 +61,107 @@ fn test_isolated_interpreter() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_resolve_origin_path() -> Result<()> {
+    let origin = std::env::current_exe()?.parent().unwrap().to_path_buf();
+
+    assert_eq!(
+        resolve_origin_path(&PathBuf::from("$ORIGIN/main.py")).unwrap(),
+        origin.join("main.py")
+    );
+    assert_eq!(
+        resolve_origin_path(&PathBuf::from("$APPDIR/lib")).unwrap(),
+        origin.join("lib")
+    );
+    assert_eq!(
+        resolve_origin_path(&PathBuf::from("main.py")).unwrap(),
+        PathBuf::from("main.py")
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_run_mode_sequence() {
+    let setup = PythonRunMode::Eval {
+        code: "import sys".to_string(),
+    };
+    let main = PythonRunMode::Module {
+        module: "main".to_string(),
+    };
+
+    let run = PythonRunMode::Sequence {
+        steps: vec![setup.clone(), main.clone()],
+    };
+    assert_eq!(run.setup_modes(), vec![&setup]);
+    assert_eq!(run.main_mode(), &main);
+
+    assert_eq!(main.setup_modes(), Vec::<&PythonRunMode>::new());
+    assert_eq!(main.main_mode(), &main);
+
+    let run = PythonRunMode::Sequence { steps: vec![] };
+    assert_eq!(run.main_mode(), &PythonRunMode::None);
+}
+
+#[test]
+fn test_run_file_origin() -> Result<()> {
+    let origin = std::env::current_exe()?.parent().unwrap().to_path_buf();
+    let filename = format!("pyembed-test-run-file-origin-{}.py", std::process::id());
+    std::fs::write(
+        origin.join(&filename),
+        "import builtins\nbuiltins.pyembed_run_file_origin = True\n",
+    )?;
+
+    let config = OxidizedPythonInterpreterConfig::default();
+    let mut interp = MainPythonInterpreter::new(config)?;
+    let py = interp.acquire_gil().unwrap();
+
+    let run = PythonRunMode::Sequence {
+        steps: vec![
+            PythonRunMode::File {
+                path: PathBuf::from("$ORIGIN").join(&filename),
+            },
+            PythonRunMode::None,
+        ],
+    };
+    let res = crate::python_eval::run(py, &run);
+    std::fs::remove_file(origin.join(&filename))?;
+    assert!(res.is_ok());
+
+    let builtins = py.import("builtins").unwrap();
+    assert!(builtins
+        .get(py, "pyembed_run_file_origin")
+        .unwrap()
+        .is_true(py)
+        .unwrap());
+
+    Ok(())
+}
+
+#[test]
+fn test_coerce_c_locale_from_python_config() {
+    let config = OxidizedPythonInterpreterConfig::from(PythonConfig::default());
+    assert_eq!(config.interpreter_config.coerce_c_locale, None);
+    assert_eq!(config.interpreter_config.coerce_c_locale_warn, None);
+
+    let config = OxidizedPythonInterpreterConfig::from(PythonConfig {
+        coerce_c_locale: Some(false),
+        ..PythonConfig::default()
+    });
+    assert_eq!(
+        config.interpreter_config.coerce_c_locale,
+        Some(CoerceCLocale::Off)
+    );
+
+    let config = OxidizedPythonInterpreterConfig::from(PythonConfig {
+        coerce_c_locale: Some(true),
+        coerce_c_locale_warn: true,
+        ..PythonConfig::default()
+    });
+    assert_eq!(
+        config.interpreter_config.coerce_c_locale,
+        Some(CoerceCLocale::LCCtype)
+    );
+    assert_eq!(config.interpreter_config.coerce_c_locale_warn, Some(true));
+}
@@ -2,9 +2,12 @@
 # License, v. 2.0. If a copy of the MPL was not distributed with this
 # file, You can obtain one at https://mozilla.org/MPL/2.0/.
 
+import hashlib
 import importlib.util
+import json
 import os
 import pathlib
+import py_compile
 import sys
 import tempfile
 import unittest
@@ -13,6 +16,8 @@ from oxidized_importer import (
     OxidizedFinder,
     OxidizedResourceCollector,
     PythonModuleBytecode,
+    PythonModuleSource,
+    PythonPackageResource,
     find_resources_in_path,
 )
 
@@ -36,6 +41,48 @@ class TestImporterResourceCollector(unittest.TestCase):
         c = OxidizedResourceCollector(policy="in-memory-only")
         self.assertEqual(c.policy, "in-memory-only")
 
+        with self.assertRaisesRegex(ValueError, "valid policies are in-memory-only"):
+            OxidizedResourceCollector(policy="bad")
+
+        with self.assertRaises(TypeError):
+            OxidizedResourceCollector(policy="in-memory-only", cache_tag=42)
+
+    def test_construct_cache_tag(self):
+        cache_tag = sys.implementation.cache_tag
+
+        c = OxidizedResourceCollector(
+            policy="filesystem-relative-only:lib", cache_tag=cache_tag
+        )
+        other = OxidizedResourceCollector(
+            policy="filesystem-relative-only:lib", cache_tag="cpython-99"
+        )
+
+        source_path = self.td / "foo.py"
+
+        with source_path.open("wb") as fh:
+            fh.write(b"import io\n")
+
+        py_compile.compile(str(source_path))
+
+        for resource in find_resources_in_path(self.td):
+            if isinstance(resource, PythonModuleBytecode):
+                c.add_filesystem_relative("", resource)
+
+                # Bytecode for another Python version is rejected.
+                with self.assertRaisesRegex(
+                    ValueError,
+                    "bytecode for foo has cache tag %s; expected cpython-99"
+                    % cache_tag,
+                ):
+                    other.add_filesystem_relative("", resource)
+
+        resources, file_installs = c.oxidize()
+        self.assertIn(
+            "__pycache__/foo.%s.pyc" % cache_tag,
+            [path.as_posix() for path, _, _ in file_installs],
+        )
+        self.assertEqual(len(other), 0)
+
     def test_source_module(self):
         c = OxidizedResourceCollector(policy="in-memory-only")
 
@@ -56,6 +103,431 @@ class TestImporterResourceCollector(unittest.TestCase):
         r = resources[0]
         self.assertEqual(r.in_memory_source, b"import io\n")
 
+    def test_add_in_memory_expected_sha256(self):
+        c = OxidizedResourceCollector(policy="in-memory-only")
+
+        with (self.td / "foo.py").open("wb") as fh:
+            fh.write(b"import io\n")
+
+        resources = find_resources_in_path(self.td)
+        self.assertEqual(len(resources), 1)
+
+        with self.assertRaisesRegex(ValueError, "sha256 mismatch"):
+            c.add_in_memory(resources[0], expected_sha256="0" * 64)
+
+        self.assertEqual(len(c), 0)
+
+        c.add_in_memory(
+            resources[0], expected_sha256=hashlib.sha256(b"import io\n").hexdigest()
+        )
+        self.assertEqual(len(c), 1)
+
+    def test_add_in_memory_many(self):
+        c = OxidizedResourceCollector(policy="in-memory-only")
+
+        for name in ("foo", "bar"):
+            with (self.td / ("%s.py" % name)).open("wb") as fh:
+                fh.write(b"import io\n")
+
+        c.add_in_memory_many(find_resources_in_path(self.td))
+
+        resources, file_installs = c.oxidize()
+        self.assertEqual(
+            {r.name for r in resources if r.name in ("foo", "bar")}, {"foo", "bar"}
+        )
+
+        with self.assertRaisesRegex(ValueError, "error adding 2 resources"):
+            c.add_in_memory_many([None, 42])
+
+    def test_add_in_memory_many_partial(self):
+        c = OxidizedResourceCollector(policy="in-memory-only")
+
+        with (self.td / "foo.py").open("wb") as fh:
+            fh.write(b"import io\n")
+
+        resources = find_resources_in_path(self.td)
+
+        def generate():
+            # Accessing the collector while it is iterating must not fail.
+            self.assertEqual(len(c), 0)
+            self.assertNotIn("foo", c)
+            yield from resources
+            yield 42
+
+        with self.assertRaisesRegex(ValueError, "error adding 1 resources"):
+            c.add_in_memory_many(generate())
+
+        self.assertIn("foo", c)
+
+    def test_policy_details(self):
+        c = OxidizedResourceCollector(policy="in-memory-only")
+        self.assertEqual(
+            c.policy_details,
+            {
+                "in_memory": True,
+                "filesystem_relative": False,
+                "filesystem_relative_prefix": None,
+            },
+        )
+
+        c = OxidizedResourceCollector(
+            policy="prefer-in-memory-fallback-filesystem-relative:lib"
+        )
+        self.assertEqual(
+            c.policy_details,
+            {
+                "in_memory": True,
+                "filesystem_relative": True,
+                "filesystem_relative_prefix": "lib",
+            },
+        )
+
+    def test_filter_by_policy(self):
+        with (self.td / "foo.py").open("wb") as fh:
+            fh.write(b"import io\n")
+
+        resources = find_resources_in_path(self.td)
+        self.assertEqual(len(resources), 1)
+
+        c = OxidizedResourceCollector(policy="in-memory-only")
+        accepted, rejected = c.filter_by_policy(resources + [None])
+        self.assertEqual(accepted, resources)
+        self.assertEqual(rejected, [None])
+        self.assertEqual(len(c), 0)
+
+        c = OxidizedResourceCollector(policy="filesystem-relative-only:lib")
+        accepted, rejected = c.filter_by_policy(resources)
+        self.assertEqual(accepted, [])
+        self.assertEqual(rejected, resources)
+        self.assertEqual(len(c), 0)
+
+    def test_remove(self):
+        c = OxidizedResourceCollector(policy="in-memory-only")
+
+        source_path = self.td / "foo.py"
+
+        with source_path.open("wb") as fh:
+            fh.write(b"import io\n")
+
+        for resource in find_resources_in_path(self.td):
+            c.add_in_memory(resource)
+
+        self.assertEqual(len(c), 1)
+        self.assertIn("foo", c)
+        self.assertNotIn("bar", c)
+        self.assertTrue(c.remove("foo"))
+        self.assertFalse(c.remove("foo"))
+        self.assertEqual(len(c), 0)
+        self.assertNotIn("foo", c)
+
+        resources, file_installs = c.oxidize()
+        self.assertEqual([r for r in resources if r.name == "foo"], [])
+
+    def test_add_direct_url(self):
+        c = OxidizedResourceCollector(policy="in-memory-only")
+
+        vcs_info = {"vcs": "git", "commit_id": "0123456789abcdef"}
+        c.add_direct_url("foo", "https://example.com/foo.git", vcs_info)
+
+        resources, file_installs = c.oxidize()
+        resources = {r.name: r for r in resources}
+
+        direct_url = resources["foo"].in_memory_distribution_resources[
+            "direct_url.json"
+        ]
+        self.assertEqual(
+            json.loads(direct_url),
+            {"url": "https://example.com/foo.git", "vcs_info": vcs_info},
+        )
+
+        c.add_direct_url("bar", "https://example.com/bar-1.0.tar.gz")
+        resources, file_installs = c.oxidize()
+        resources = {r.name: r for r in resources}
+
+        direct_url = resources["bar"].in_memory_distribution_resources[
+            "direct_url.json"
+        ]
+        self.assertEqual(json.loads(direct_url)["archive_info"], {})
+
+    def test_add_direct_url_file(self):
+        c = OxidizedResourceCollector(policy="in-memory-only")
+
+        wheel_path = self.td / "foo-1.0-py3-none-any.whl"
+        with wheel_path.open("wb") as fh:
+            fh.write(b"")
+
+        c.add_direct_url("foo", wheel_path.as_uri())
+        c.add_direct_url("bar", self.td.absolute().as_uri())
+
+        resources, file_installs = c.oxidize()
+        resources = {r.name: r for r in resources}
+
+        direct_url = resources["foo"].in_memory_distribution_resources[
+            "direct_url.json"
+        ]
+        self.assertEqual(
+            json.loads(direct_url),
+            {"url": wheel_path.as_uri(), "archive_info": {}},
+        )
+
+        direct_url = resources["bar"].in_memory_distribution_resources[
+            "direct_url.json"
+        ]
+        self.assertEqual(
+            json.loads(direct_url),
+            {"url": self.td.absolute().as_uri(), "dir_info": {}},
+        )
+
+    def test_add_filesystem_relative_absolute_prefix(self):
+        c = OxidizedResourceCollector(policy="filesystem-relative-only:lib")
+
+        with (self.td / "foo.py").open("wb") as fh:
+            fh.write(b"import io\n")
+
+        resources = find_resources_in_path(self.td)
+        self.assertEqual(len(resources), 1)
+
+        with self.assertRaisesRegex(ValueError, "prefix must be a relative path"):
+            c.add_filesystem_relative(str(self.td.absolute()), resources[0])
+
+        c.add_filesystem_relative("lib", resources[0])
+
+    def test_add_bytecode_invalid_optimize_level(self):
+        c = OxidizedResourceCollector(policy="in-memory-only")
+
+        cache_tag = sys.implementation.cache_tag
+
+        path = self.td / "__pycache__" / ("foo.%s.pyc" % cache_tag)
+        path.parent.mkdir()
+
+        with path.open("wb") as fh:
+            fh.write(b"0123456789abcdefbytecode")
+
+        resources = find_resources_in_path(self.td)
+        self.assertEqual(len(resources), 1)
+        r = resources[0]
+
+        with self.assertRaisesRegex(
+            ValueError, "optimize_level must be 0, 1, or 2: got 3"
+        ):
+            r.optimize_level = 3
+
+        self.assertEqual(r.optimize_level, 0)
+
+        # The rejected assignment leaves the bytecode packaged at level 0.
+        c.add_in_memory(r)
+
+        resources, file_installs = c.oxidize()
+        resources = {r.name: r for r in resources}
+        self.assertEqual(resources["foo"].in_memory_bytecode, b"bytecode")
+        self.assertIsNone(resources["foo"].in_memory_bytecode_opt1)
+        self.assertIsNone(resources["foo"].in_memory_bytecode_opt2)
+
+    def test_add_missing_parents(self):
+        c = OxidizedResourceCollector(policy="in-memory-only")
+
+        source_path = self.td / "a" / "b" / "c.py"
+        source_path.parent.mkdir(parents=True)
+
+        with source_path.open("wb") as fh:
+            fh.write(b"import io\n")
+
+        resources = find_resources_in_path(self.td)
+        self.assertEqual([r.module for r in resources], ["a.b.c"])
+
+        c.add_in_memory(resources[0])
+
+        resources, file_installs = c.oxidize()
+        resources = {r.name: r for r in resources}
+
+        self.assertEqual(set(resources.keys()), {"a", "a.b", "a.b.c"})
+        self.assertTrue(resources["a"].is_package)
+        self.assertTrue(resources["a.b"].is_package)
+        self.assertEqual(resources["a"].in_memory_source, b"")
+        self.assertEqual(resources["a.b.c"].in_memory_source, b"import io\n")
+
+    def test_package_resource(self):
+        c = OxidizedResourceCollector(policy="in-memory-only")
+
+        package_path = self.td / "foo"
+        package_path.mkdir()
+
+        with (package_path / "__init__.py").open("wb") as fh:
+            fh.write(b"")
+        with (package_path / "data.txt").open("wb") as fh:
+            fh.write(b"data")
+
+        for resource in find_resources_in_path(self.td):
+            c.add_in_memory(resource)
+
+        resources, file_installs = c.oxidize()
+        resources = {r.name: r for r in resources}
+        self.assertEqual(
+            resources["foo"].in_memory_package_resources, {"data.txt": b"data"}
+        )
+
+        resources = [
+            r
+            for r in find_resources_in_path(self.td)
+            if isinstance(r, PythonPackageResource)
+        ]
+        self.assertEqual(len(resources), 1)
+
+        with self.assertRaisesRegex(
+            ValueError, "error adding resource data.txt in package foo"
+        ):
+            c.add_filesystem_relative("", resources[0])
+
+    def test_validate_orphan_resource(self):
+        c = OxidizedResourceCollector(policy="in-memory-only")
+
+        resource_path = self.td / "foo" / "resource.txt"
+        resource_path.parent.mkdir()
+
+        with resource_path.open("wb") as fh:
+            fh.write(b"data")
+
+        resources = find_resources_in_path(self.td)
+        self.assertEqual(len(resources), 1)
+        c.add_in_memory(resources[0])
+
+        self.assertEqual(c.validate(), ["foo"])
+
+        init_path = self.td / "foo" / "__init__.py"
+        with init_path.open("wb") as fh:
+            fh.write(b"")
+
+        for resource in find_resources_in_path(self.td):
+            if isinstance(resource, PythonModuleSource):
+                c.add_in_memory(resource)
+
+        self.assertEqual(c.validate(), [])
+
+    def test_equals(self):
+        for name in ("foo", "bar"):
+            with (self.td / ("%s.py" % name)).open("wb") as fh:
+                fh.write(b"import io\n")
+
+        resources = find_resources_in_path(self.td)
+        self.assertEqual(len(resources), 2)
+
+        c1 = OxidizedResourceCollector(policy="in-memory-only")
+        c2 = OxidizedResourceCollector(policy="in-memory-only")
+
+        for resource in resources:
+            c1.add_in_memory(resource)
+        for resource in reversed(resources):
+            c2.add_in_memory(resource)
+
+        self.assertTrue(c1.equals(c2))
+        self.assertTrue(c2.equals(c1))
+
+        c2.remove("foo")
+        self.assertFalse(c1.equals(c2))
+
+    def test_merge(self):
+        for name in ("foo", "bar"):
+            with (self.td / ("%s.py" % name)).open("wb") as fh:
+                fh.write(b"import io\n")
+
+        resources = {r.name: r for r in find_resources_in_path(self.td)}
+
+        c1 = OxidizedResourceCollector(policy="in-memory-only")
+        c1.add_in_memory(resources["foo"])
+        c2 = OxidizedResourceCollector(policy="in-memory-only")
+        c2.add_in_memory(resources["bar"])
+
+        c1.merge(c2)
+        self.assertEqual(len(c1), 2)
+        self.assertEqual(len(c2), 1)
+
+        with self.assertRaisesRegex(ValueError, "bar is already present"):
+            c1.merge(c2)
+
+        c1.merge(c2, overwrite=True)
+        self.assertEqual(len(c1), 2)
+
+        c3 = OxidizedResourceCollector(policy="filesystem-relative-only:lib")
+        with self.assertRaisesRegex(ValueError, "does not allow in-memory"):
+            c3.merge(c1)
+        self.assertEqual(len(c3), 0)
+
+    def test_to_frozen_c_array(self):
+        c = OxidizedResourceCollector(policy="in-memory-only")
+
+        source_path = self.td / "foo.py"
+
+        with source_path.open("wb") as fh:
+            fh.write(b"import io\n")
+
+        py_compile.compile(str(source_path))
+
+        for resource in find_resources_in_path(self.td):
+            if isinstance(resource, PythonModuleBytecode):
+                c.add_in_memory(resource)
+
+        source = c.to_frozen_c_array("frozen_modules")
+        self.assertIn("const struct _frozen frozen_modules[] = {", source)
+        self.assertIn('{"foo", frozen_modules_0, ', source)
+        self.assertIn("{0, 0, 0}", source)
+
+        with self.assertRaisesRegex(ValueError, "is not a valid C identifier"):
+            c.to_frozen_c_array("not valid")
+
+    def test_to_cas(self):
+        for name in ("foo", "bar"):
+            with (self.td / ("%s.py" % name)).open("wb") as fh:
+                fh.write(b"import io\n")
+
+        c = OxidizedResourceCollector(policy="in-memory-only")
+        for resource in find_resources_in_path(self.td):
+            c.add_in_memory(resource)
+
+        blobs, manifest = c.to_cas()
+
+        digest = hashlib.sha256(b"import io\n").hexdigest()
+        self.assertEqual(manifest["foo"], {"in_memory_source": digest})
+        self.assertEqual(manifest["bar"], {"in_memory_source": digest})
+        self.assertEqual(blobs, {digest: b"import io\n"})
+
+    def test_to_cas_filesystem_relative(self):
+        with (self.td / "foo.py").open("wb") as fh:
+            fh.write(b"import io\n")
+
+        c = OxidizedResourceCollector(policy="filesystem-relative-only:lib")
+        for resource in find_resources_in_path(self.td):
+            c.add_filesystem_relative("", resource)
+
+        blobs, manifest = c.to_cas()
+
+        digest = hashlib.sha256(b"import io\n").hexdigest()
+        self.assertEqual(manifest["foo"], {"relative_path_module_source": digest})
+        self.assertEqual(blobs, {digest: b"import io\n"})
+
+    def test_snapshot_restore(self):
+        c = OxidizedResourceCollector(policy="in-memory-only")
+
+        source_path = self.td / "foo.py"
+
+        with source_path.open("wb") as fh:
+            fh.write(b"import io\n")
+
+        token = c.snapshot()
+
+        for resource in find_resources_in_path(self.td):
+            c.add_in_memory(resource)
+
+        resources, file_installs = c.oxidize()
+        self.assertEqual(len([r for r in resources if r.name == "foo"]), 1)
+
+        c.restore(token)
+
+        resources, file_installs = c.oxidize()
+        self.assertEqual([r for r in resources if r.name == "foo"], [])
+
+        with self.assertRaises(TypeError):
+            c.restore(None)
+
     def test_add_sys_path(self):
         c = OxidizedResourceCollector(
             policy="prefer-in-memory-fallback-filesystem-relative:prefix"
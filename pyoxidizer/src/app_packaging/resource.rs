@@ -132,6 +132,41 @@ impl FileManifest {
         self.files.iter()
     }
 
+    /// Remove files whose relative path matches any of the given glob patterns.
+    ///
+    /// Patterns are evaluated against the relative paths in this manifest.
+    /// `*` does not match across directory separators; use `**` for that.
+    ///
+    /// Returns the paths that were removed.
+    pub fn remove_files(&mut self, patterns: &[String]) -> Result<Vec<PathBuf>> {
+        let patterns = patterns
+            .iter()
+            .map(|p| glob::Pattern::new(p))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let options = glob::MatchOptions {
+            require_literal_separator: true,
+            ..glob::MatchOptions::default()
+        };
+
+        let removed = self
+            .files
+            .keys()
+            .filter(|path| {
+                patterns
+                    .iter()
+                    .any(|pattern| pattern.matches_path_with(path, options))
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+
+        for path in &removed {
+            self.files.remove(path);
+        }
+
+        Ok(removed)
+    }
+
     /// Whether this manifest contains the specified file path.
     pub fn has_path(&self, path: &Path) -> bool {
         self.files.contains_key(path)
@@ -205,6 +240,32 @@ mod tests {
         assert!(res.is_err());
     }
 
+    #[test]
+    fn test_remove_files() {
+        let mut v = FileManifest::default();
+        let f = FileContent {
+            data: vec![],
+            executable: false,
+        };
+
+        v.add_file(&PathBuf::from("foo.so"), &f).unwrap();
+        v.add_file(&PathBuf::from("foo.so.debug"), &f).unwrap();
+        v.add_file(&PathBuf::from("lib/bar.so.debug"), &f).unwrap();
+
+        let removed = v.remove_files(&["*.so.debug".to_string()]).unwrap();
+        assert_eq!(removed, vec![PathBuf::from("foo.so.debug")]);
+        assert!(v.has_path(&PathBuf::from("lib/bar.so.debug")));
+
+        let removed = v.remove_files(&["**/*.so.debug".to_string()]).unwrap();
+        assert_eq!(removed, vec![PathBuf::from("lib/bar.so.debug")]);
+
+        let entries = v.entries().collect_vec();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].0, &PathBuf::from("foo.so"));
+
+        assert!(v.remove_files(&["[".to_string()]).is_err());
+    }
+
     #[test]
     fn test_relative_directories() {
         let mut v = FileManifest::default();
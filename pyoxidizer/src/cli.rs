@@ -166,6 +166,25 @@ pub fn run_cli() -> Result<()> {
                         .help("Path to project to evaluate"),
                 ),
         )
+        .subcommand(
+            SubCommand::with_name("validate-config")
+                .about("Validate a configuration file without building anything")
+                .arg(
+                    Arg::with_name("target_triple")
+                        .long("target-triple")
+                        .takes_value(true)
+                        .help(
+                            "Rust target triple to evaluate for \
+                             (defaults to $PYOXIDIZER_TARGET_TRIPLE or the host)",
+                        ),
+                )
+                .arg(
+                    Arg::with_name("path")
+                        .default_value(".")
+                        .value_name("PATH")
+                        .help("Path to project to evaluate"),
+                ),
+        )
         .subcommand(
             SubCommand::with_name("build")
                 .setting(AppSettings::ArgRequiredElseHelp)
@@ -175,7 +194,10 @@ pub fn run_cli() -> Result<()> {
                     Arg::with_name("target_triple")
                         .long("target-triple")
                         .takes_value(true)
-                        .help("Rust target triple to build for"),
+                        .help(
+                            "Rust target triple to build for \
+                             (defaults to $PYOXIDIZER_TARGET_TRIPLE or the host)",
+                        ),
                 )
                 .arg(
                     Arg::with_name("release")
@@ -205,7 +227,10 @@ pub fn run_cli() -> Result<()> {
                     Arg::with_name("target_triple")
                         .long("target-triple")
                         .takes_value(true)
-                        .help("Rust target triple to build for"),
+                        .help(
+                            "Rust target triple to build for \
+                             (defaults to $PYOXIDIZER_TARGET_TRIPLE or the host)",
+                        ),
                 )
                 .arg(
                     Arg::with_name("release")
@@ -329,6 +354,13 @@ pub fn run_cli() -> Result<()> {
             projectmgmt::list_targets(&logger_context.logger, Path::new(path))
         }
 
+        ("validate-config", Some(args)) => {
+            let target_triple = args.value_of("target_triple");
+            let path = args.value_of("path").unwrap();
+
+            projectmgmt::validate_config(&logger_context.logger, Path::new(path), target_triple)
+        }
+
         ("init-rust-project", Some(args)) => {
             let path = args.value_of("path").unwrap();
             let project_path = Path::new(path);
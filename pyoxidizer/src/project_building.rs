@@ -152,6 +152,10 @@ pub fn build_executable_with_rust_project(
         features.push("jemalloc");
     }
 
+    if exe.requires_mimalloc() {
+        features.push("mimalloc");
+    }
+
     let features = features.join(" ");
 
     if !features.is_empty() {
@@ -10,6 +10,7 @@ use {
     crate::py_packaging::standalone_distribution::StandaloneDistribution,
     crate::starlark::eval::{eval_starlark_config_file, EvalResult},
     anyhow::{anyhow, Result},
+    std::collections::BTreeSet,
     std::fs::create_dir_all,
     std::io::{Cursor, Read},
     std::path::Path,
@@ -29,11 +30,30 @@ pub fn default_target() -> Result<String> {
     }
 }
 
+/// Environment variable defining the target triple to build for.
+const TARGET_TRIPLE_ENV: &str = "PYOXIDIZER_TARGET_TRIPLE";
+
+/// Resolve the target triple to build for.
+///
+/// An explicit `target` wins. Otherwise the `PYOXIDIZER_TARGET_TRIPLE`
+/// environment variable is consulted, falling back to the default target
+/// for the current host.
 pub fn resolve_target(target: Option<&str>) -> Result<String> {
+    resolve_target_with_env(target, std::env::var(TARGET_TRIPLE_ENV).ok())
+}
+
+fn resolve_target_with_env(target: Option<&str>, env_target: Option<String>) -> Result<String> {
     if let Some(s) = target {
         Ok(s.to_string())
+    } else if let Some(s) = env_target.filter(|s| !s.is_empty()) {
+        Ok(s)
     } else {
-        default_target()
+        default_target().or_else(|_| {
+            Err(anyhow!(
+                "unable to resolve target triple; specify --target-triple or set {}",
+                TARGET_TRIPLE_ENV
+            ))
+        })
     }
 }
 
@@ -45,7 +65,7 @@ pub fn list_targets(logger: &slog::Logger, project_path: &Path) -> Result<()> {
         )
     })?;
 
-    let target_triple = default_target()?;
+    let target_triple = resolve_target(None)?;
     let res = eval_starlark_config_file(
         logger,
         &config_path,
@@ -73,6 +93,101 @@ pub fn list_targets(logger: &slog::Logger, project_path: &Path) -> Result<()> {
     Ok(())
 }
 
+/// Evaluate a config file and validate it without building anything.
+///
+/// No targets are resolved. Problems that would prevent targets from being
+/// resolved are errors. Non-fatal problems are returned as warnings, which
+/// are one of:
+///
+/// * `no targets are registered`
+/// * `target <name> is not reachable from the default target`
+pub fn validate_config_file(
+    logger: &slog::Logger,
+    config_path: &Path,
+    target_triple: &str,
+) -> Result<Vec<String>> {
+    let res = eval_starlark_config_file(
+        logger,
+        config_path,
+        target_triple,
+        false,
+        false,
+        Some(Vec::new()),
+        false,
+    )?;
+
+    let context = &res.context;
+
+    for (name, target) in &context.targets {
+        for depend in &target.depends {
+            if depend == name {
+                return Err(anyhow!("target {} depends on itself", name));
+            } else if !context.targets.contains_key(depend) {
+                return Err(anyhow!(
+                    "target {} depends on unregistered target {}",
+                    name,
+                    depend
+                ));
+            }
+        }
+    }
+
+    let mut warnings = Vec::new();
+
+    if let Some(default_target) = &context.default_target {
+        let mut reachable = BTreeSet::new();
+        let mut pending = vec![default_target.clone()];
+
+        while let Some(name) = pending.pop() {
+            if reachable.insert(name.clone()) {
+                pending.extend(context.targets[&name].depends.iter().cloned());
+            }
+        }
+
+        for name in &context.targets_order {
+            if !reachable.contains(name) {
+                warnings.push(format!(
+                    "target {} is not reachable from the default target",
+                    name
+                ));
+            }
+        }
+    } else {
+        warnings.push("no targets are registered".to_string());
+    }
+
+    Ok(warnings)
+}
+
+/// Validate the config file of a project and print warnings.
+pub fn validate_config(
+    logger: &slog::Logger,
+    project_path: &Path,
+    target_triple: Option<&str>,
+) -> Result<()> {
+    let config_path = find_pyoxidizer_config_file_env(logger, project_path).ok_or_else(|| {
+        anyhow!(
+            "unable to find PyOxidizer config file at {}",
+            project_path.display()
+        )
+    })?;
+    let target_triple = resolve_target(target_triple)?;
+
+    let warnings = validate_config_file(logger, &config_path, &target_triple)?;
+
+    for warning in &warnings {
+        println!("warning: {}", warning);
+    }
+
+    println!(
+        "{} is valid ({} warnings)",
+        config_path.display(),
+        warnings.len()
+    );
+
+    Ok(())
+}
+
 /// Build a PyOxidizer enabled project.
 ///
 /// This is a glorified wrapper around `cargo build`. Our goal is to get the
@@ -363,3 +478,70 @@ pub fn python_distribution_licenses(path: &str) -> Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::testutil::*};
+
+    #[test]
+    fn test_resolve_target_with_env() -> Result<()> {
+        assert_eq!(
+            resolve_target_with_env(Some("explicit"), Some("env".to_string()))?,
+            "explicit"
+        );
+        assert_eq!(
+            resolve_target_with_env(None, Some("env".to_string()))?,
+            "env"
+        );
+
+        if let Ok(default) = default_target() {
+            assert_eq!(resolve_target_with_env(None, None)?, default);
+            assert_eq!(
+                resolve_target_with_env(None, Some("".to_string()))?,
+                default
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_config_file() -> Result<()> {
+        let logger = get_logger()?;
+        let temp_dir = tempdir::TempDir::new("pyoxidizer-test")?;
+        let config_path = temp_dir.path().join("pyoxidizer.bzl");
+        let target_triple = resolve_target(None)?;
+
+        std::fs::write(&config_path, "")?;
+        assert_eq!(
+            validate_config_file(&logger, &config_path, &target_triple)?,
+            vec!["no targets are registered".to_string()]
+        );
+
+        std::fs::write(
+            &config_path,
+            "def noop():\n    pass\n\n\
+             register_target('a', noop, depends=['b'])\n\
+             register_target('b', noop)\n\
+             register_target('c', noop)\n\
+             resolve_targets()\n",
+        )?;
+        assert_eq!(
+            validate_config_file(&logger, &config_path, &target_triple)?,
+            vec!["target c is not reachable from the default target".to_string()]
+        );
+
+        std::fs::write(
+            &config_path,
+            "def noop():\n    pass\n\n\
+             register_target('a', noop, depends=['missing'])\n",
+        )?;
+        let err = validate_config_file(&logger, &config_path, &target_triple).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "target a depends on unregistered target missing"
+        );
+
+        Ok(())
+    }
+}
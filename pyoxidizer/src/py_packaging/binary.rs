@@ -17,7 +17,7 @@ use {
         PythonExtensionModule, PythonModuleBytecodeFromSource, PythonModuleSource,
         PythonPackageDistributionResource, PythonPackageResource, PythonResource,
     },
-    python_packaging::resource_collection::PythonResourcesPolicy,
+    python_packaging::resource_collection::{PythonResourcesPolicy, ResourceDataKind},
     std::collections::{BTreeMap, HashMap},
     std::convert::TryFrom,
     std::fs::File,
@@ -55,6 +55,9 @@ pub trait PythonBinaryBuilder {
     /// Obtain the `PythonResourcesPolicy` for the builder.
     fn python_resources_policy(&self) -> &PythonResourcesPolicy;
 
+    /// The tag to apply to bytecode files, e.g. `cpython-38`.
+    fn cache_tag(&self) -> &str;
+
     /// Path to Python executable that can be used to derive info at build time.
     ///
     /// The produced binary is effectively a clone of the Python distribution behind the
@@ -265,16 +268,26 @@ pub trait PythonBinaryBuilder {
     /// `files` is files to read names from.
     ///
     /// `glob_patterns` is file patterns of files to read names from.
+    ///
+    /// `kinds` limits filtering to specific kinds of resource data. If not
+    /// defined, filtered resources are removed entirely.
+    ///
+    /// `ignore_missing` skips filter files that don't exist instead of erroring.
     fn filter_resources_from_files(
         &mut self,
         logger: &slog::Logger,
         files: &[&Path],
         glob_patterns: &[&str],
+        kinds: Option<&[ResourceDataKind]>,
+        ignore_missing: bool,
     ) -> Result<()>;
 
     /// Whether the binary requires the jemalloc library.
     fn requires_jemalloc(&self) -> bool;
 
+    /// Whether the binary requires the mimalloc library.
+    fn requires_mimalloc(&self) -> bool;
+
     /// Obtain an `EmbeddedPythonBinaryData` instance from this one.
     fn as_embedded_python_binary_data(
         &self,
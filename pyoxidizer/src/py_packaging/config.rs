@@ -22,6 +22,7 @@ pub fn default_raw_allocator(target_triple: &str) -> RawAllocator {
 #[derive(Clone, Debug, PartialEq)]
 pub enum RawAllocator {
     Jemalloc,
+    Mimalloc,
     Rust,
     System,
 }
@@ -33,6 +34,15 @@ pub enum RunMode {
     Module { module: String },
     Eval { code: String },
     File { path: String },
+    Sequence { steps: Vec<RunMode> },
+}
+
+/// How the legacy C locale is coerced at interpreter startup.
+#[derive(Clone, Debug, PartialEq)]
+pub enum CoerceCLocale {
+    Off,
+    On,
+    Warn,
 }
 
 /// How the `terminfo` database is resolved at run-time.
@@ -41,13 +51,17 @@ pub enum TerminfoResolution {
     Dynamic,
     None,
     Static(String),
+    SystemPaths,
 }
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct EmbeddedPythonConfig {
     pub bytes_warning: i32,
+    pub coerce_c_locale: Option<CoerceCLocale>,
+    pub development_mode: bool,
     pub ignore_environment: bool,
     pub inspect: bool,
+    pub int_max_str_digits: Option<i64>,
     pub interactive: bool,
     pub isolated: bool,
     pub legacy_windows_fs_encoding: bool,
@@ -69,16 +83,21 @@ pub struct EmbeddedPythonConfig {
     pub use_hash_seed: bool,
     pub user_site_directory: bool,
     pub verbose: i32,
+    pub warn_default_encoding: bool,
     pub write_bytecode: bool,
     pub write_modules_directory_env: Option<String>,
+    pub write_modules_directory: Option<String>,
 }
 
 impl Default for EmbeddedPythonConfig {
     fn default() -> Self {
         EmbeddedPythonConfig {
             bytes_warning: 0,
+            coerce_c_locale: None,
+            development_mode: false,
             ignore_environment: true,
             inspect: false,
+            int_max_str_digits: None,
             interactive: false,
             isolated: true,
             legacy_windows_fs_encoding: false,
@@ -91,6 +110,7 @@ impl Default for EmbeddedPythonConfig {
             unbuffered_stdio: false,
             use_hash_seed: false,
             verbose: 0,
+            warn_default_encoding: false,
             filesystem_importer: false,
             site_import: false,
             sys_frozen: false,
@@ -102,6 +122,7 @@ impl Default for EmbeddedPythonConfig {
             user_site_directory: false,
             write_bytecode: false,
             write_modules_directory_env: None,
+            write_modules_directory: None,
         }
     }
 }
@@ -57,6 +57,82 @@ pub fn is_stdlib_test_package(name: &str) -> bool {
     false
 }
 
+/// Standard library modules the interpreter imports during initialization.
+///
+/// Packages include all their submodules. Excluding any of these would
+/// produce a binary that can't start.
+const STDLIB_BOOTSTRAP_MODULES: &[&str] = &[
+    "_collections_abc",
+    "abc",
+    "codecs",
+    "encodings",
+    "genericpath",
+    "io",
+    "ntpath",
+    "os",
+    "posixpath",
+    "stat",
+];
+
+/// Selects standard library modules by name using glob patterns.
+///
+/// A pattern matches a module if it matches the module's name or the name of
+/// any of its parent packages. e.g. `tkinter` matches both `tkinter` and
+/// `tkinter.ttk`.
+///
+/// If any include patterns are defined, only modules matching one of them
+/// are selected. Modules matching an exclude pattern are never selected.
+/// Modules required to initialize the interpreter are always selected.
+#[derive(Clone, Debug, Default)]
+pub struct StdlibModuleFilter {
+    includes: Vec<glob::Pattern>,
+    excludes: Vec<glob::Pattern>,
+}
+
+impl StdlibModuleFilter {
+    pub fn new(includes: &[String], excludes: &[String]) -> Result<Self> {
+        let parse = |patterns: &[String]| -> Result<Vec<glob::Pattern>> {
+            patterns
+                .iter()
+                .map(|p| {
+                    glob::Pattern::new(p)
+                        .map_err(|e| anyhow!("invalid module name pattern {}: {}", p, e))
+                })
+                .collect()
+        };
+
+        Ok(Self {
+            includes: parse(includes)?,
+            excludes: parse(excludes)?,
+        })
+    }
+
+    /// Whether a module having the given name should be included.
+    pub fn matches(&self, name: &str) -> bool {
+        let top_level = name.split('.').next().unwrap_or(name);
+        if STDLIB_BOOTSTRAP_MODULES.contains(&top_level) {
+            return true;
+        }
+
+        let matches_any = |patterns: &[glob::Pattern]| {
+            let mut candidate = name;
+
+            loop {
+                if patterns.iter().any(|p| p.matches(candidate)) {
+                    return true;
+                }
+
+                match candidate.rfind('.') {
+                    Some(idx) => candidate = &candidate[0..idx],
+                    None => return false,
+                }
+            }
+        };
+
+        (self.includes.is_empty() || matches_any(&self.includes)) && !matches_any(&self.excludes)
+    }
+}
+
 /// Denotes how a binary should link libpython.
 #[derive(Clone, Debug, PartialEq)]
 pub enum BinaryLibpythonLinkMode {
@@ -93,8 +169,19 @@ impl TryFrom<&str> for ExtensionModuleFilter {
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum PythonDistributionLocation {
-    Local { local_path: String, sha256: String },
-    Url { url: String, sha256: String },
+    Local {
+        local_path: String,
+        sha256: String,
+    },
+    /// A distribution obtainable from a URL.
+    ///
+    /// `mirror_urls` are tried in order if fetching from `url` fails. All URLs
+    /// must serve content having the same SHA-256.
+    Url {
+        url: String,
+        sha256: String,
+        mirror_urls: Vec<String>,
+    },
 }
 
 /// Describes an obtainable Python distribution.
@@ -106,6 +193,9 @@ pub struct PythonDistributionRecord {
     /// Rust target triple this distribution runs on.
     pub target_triple: String,
 
+    /// Python major.minor version of the distribution, e.g. `3.8`.
+    pub python_major_minor_version: String,
+
     /// Whether the distribution can load prebuilt extension modules.
     pub supports_prebuilt_extension_modules: bool,
 }
@@ -147,18 +237,23 @@ pub trait PythonDistribution {
         resources_policy: &PythonResourcesPolicy,
         config: &EmbeddedPythonConfig,
         extension_module_filter: &ExtensionModuleFilter,
-        preferred_extension_module_variants: Option<HashMap<String, String>>,
+        preferred_extension_module_variants: Option<HashMap<String, Vec<String>>>,
         include_sources: bool,
         include_resources: bool,
         include_test: bool,
+        stdlib_filter: &StdlibModuleFilter,
     ) -> Result<Box<dyn PythonBinaryBuilder>>;
 
     /// Obtain extension modules matching a specified filter and variant selection preferences.
+    ///
+    /// Extension modules not matching `stdlib_filter` are excluded unless they
+    /// are required or built in by default.
     fn filter_extension_modules(
         &self,
         logger: &slog::Logger,
         filter: &ExtensionModuleFilter,
-        preferred_variants: Option<HashMap<String, String>>,
+        preferred_variants: Option<HashMap<String, Vec<String>>>,
+        stdlib_filter: &StdlibModuleFilter,
     ) -> Result<Vec<DistributionExtensionModule>>;
 
     /// Obtain `SourceModule` instances present in this distribution.
@@ -390,8 +485,24 @@ pub fn resolve_python_distribution_archive(
             let p = PathBuf::from(local_path);
             copy_local_distribution(&p, sha256, cache_dir)
         }
-        PythonDistributionLocation::Url { url, sha256 } => {
-            download_distribution(url, sha256, cache_dir)
+        PythonDistributionLocation::Url {
+            url,
+            sha256,
+            mirror_urls,
+        } => {
+            let mut errors = Vec::new();
+
+            for url in std::iter::once(url).chain(mirror_urls.iter()) {
+                match download_distribution(url, sha256, cache_dir) {
+                    Ok(path) => return Ok(path),
+                    Err(e) => errors.push(format!("{}: {}", url, e)),
+                }
+            }
+
+            Err(anyhow!(
+                "unable to obtain Python distribution:\n{}",
+                errors.join("\n")
+            ))
         }
     }
 }
@@ -467,13 +578,16 @@ pub fn resolve_distribution(
 }
 
 /// Resolve the location of the default Python distribution of a given flavor and build target.
+///
+/// If `python_major_minor_version` is defined, the distribution must be of
+/// that Python version.
 pub fn default_distribution_location(
     flavor: &DistributionFlavor,
     target: &str,
+    python_major_minor_version: Option<&str>,
 ) -> Result<PythonDistributionLocation> {
-    let dist = PYTHON_DISTRIBUTIONS
-        .find_distribution(target, flavor)
-        .ok_or_else(|| anyhow!("could not find default Python distribution for {}", target))?;
+    let dist =
+        PYTHON_DISTRIBUTIONS.find_distribution(target, flavor, python_major_minor_version)?;
 
     Ok(dist.location)
 }
@@ -491,7 +605,7 @@ pub fn default_distribution(
     target: &str,
     dest_dir: &Path,
 ) -> Result<Box<dyn PythonDistribution>> {
-    let location = default_distribution_location(flavor, target)?;
+    let location = default_distribution_location(flavor, target, None)?;
 
     resolve_distribution(logger, flavor, &location, dest_dir)
 }
@@ -559,4 +673,46 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_stdlib_module_filter() -> Result<()> {
+        let filter = StdlibModuleFilter::default();
+        assert!(filter.matches("tkinter"));
+        assert!(filter.matches("tkinter.ttk"));
+
+        let filter = StdlibModuleFilter::new(&[], &["tkinter".to_string(), "turtle*".to_string()])?;
+        assert!(!filter.matches("tkinter"));
+        assert!(!filter.matches("tkinter.ttk"));
+        assert!(!filter.matches("turtledemo.clock"));
+        assert!(filter.matches("tkinterfoo"));
+        assert!(filter.matches("json"));
+
+        let filter = StdlibModuleFilter::new(
+            &["json".to_string(), "email.*".to_string()],
+            &["email.mime".to_string()],
+        )?;
+        assert!(filter.matches("json"));
+        assert!(filter.matches("json.decoder"));
+        assert!(!filter.matches("email"));
+        assert!(filter.matches("email.parser"));
+        assert!(!filter.matches("email.mime.text"));
+        assert!(!filter.matches("xml"));
+
+        assert!(StdlibModuleFilter::new(&["[".to_string()], &[]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_stdlib_module_filter_bootstrap() -> Result<()> {
+        let filter = StdlibModuleFilter::new(&["json".to_string()], &["encodings*".to_string()])?;
+        assert!(filter.matches("encodings"));
+        assert!(filter.matches("encodings.utf_8"));
+        assert!(filter.matches("codecs"));
+        assert!(filter.matches("io"));
+        assert!(filter.matches("os"));
+        assert!(!filter.matches("email"));
+
+        Ok(())
+    }
 }
@@ -16,7 +16,7 @@ use {
         PythonPackageDistributionResource, PythonPackageResource,
     },
     python_packaging::resource_collection::{
-        PreparedPythonResources, PythonResourceCollector, PythonResourcesPolicy,
+        PreparedPythonResources, PythonResourceCollector, PythonResourcesPolicy, ResourceDataKind,
     },
     slog::{info, warn},
     std::collections::{BTreeMap, BTreeSet},
@@ -372,27 +372,55 @@ impl PrePackagedResources {
     }
 
     /// Filter the entities in this instance against names in files.
+    ///
+    /// If `kinds` is defined, only data of the specified kinds is removed from
+    /// filtered entities. Otherwise, filtered entities are removed entirely.
+    ///
+    /// If `ignore_missing` is true, missing filter files are skipped. If no
+    /// filter files exist, nothing is filtered.
     pub fn filter_from_files(
         &mut self,
         logger: &slog::Logger,
         files: &[&Path],
         glob_patterns: &[&str],
+        kinds: Option<&[ResourceDataKind]>,
+        ignore_missing: bool,
     ) -> Result<()> {
-        let resource_names = resolve_resource_names_from_files(files, glob_patterns)?;
+        let resource_names =
+            match resolve_resource_names_from_files(files, glob_patterns, ignore_missing)? {
+                Some(names) => names,
+                None => {
+                    warn!(logger, "no filter files found; not filtering");
+                    return Ok(());
+                }
+            };
 
         warn!(logger, "filtering module entries");
 
-        self.collector.filter_resources_mut(|resource| {
-            if !resource_names.contains(&resource.name) {
-                warn!(logger, "removing {}", resource.name);
-                false
-            } else {
-                true
-            }
-        })?;
+        if let Some(kinds) = kinds {
+            self.collector.filter_resource_data_mut(kinds, |resource| {
+                if !resource_names.contains(&resource.name) {
+                    warn!(logger, "removing {:?} data from {}", kinds, resource.name);
+                    false
+                } else {
+                    true
+                }
+            })?;
+        } else {
+            self.collector.filter_resources_mut(|resource| {
+                if !resource_names.contains(&resource.name) {
+                    warn!(logger, "removing {}", resource.name);
+                    false
+                } else {
+                    true
+                }
+            })?;
+        }
 
-        warn!(logger, "filtering embedded extension modules");
-        filter_btreemap(logger, &mut self.extension_module_states, &resource_names);
+        if kinds.map_or(true, |kinds| kinds.contains(&ResourceDataKind::Extension)) {
+            warn!(logger, "filtering embedded extension modules");
+            filter_btreemap(logger, &mut self.extension_module_states, &resource_names);
+        }
 
         Ok(())
     }
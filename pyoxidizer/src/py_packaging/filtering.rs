@@ -14,7 +14,7 @@ use std::io::{BufRead, BufReader};
 use std::path::Path;
 
 pub fn read_resource_names_file(path: &Path) -> Result<BTreeSet<String>> {
-    let fh = File::open(path)?;
+    let fh = File::open(path).map_err(|e| anyhow!("unable to open {}: {}", path.display(), e))?;
 
     let mut res: BTreeSet<String> = BTreeSet::new();
 
@@ -31,15 +31,28 @@ pub fn read_resource_names_file(path: &Path) -> Result<BTreeSet<String>> {
     Ok(res)
 }
 
+/// Resolve resource names from filter files and glob patterns of filter files.
+///
+/// If `ignore_missing` is true, files that don't exist and glob patterns not
+/// matching any files are skipped instead of raising an error. `None` is
+/// returned if no filter files were read, in which case no filtering should
+/// be performed.
 pub fn resolve_resource_names_from_files(
     files: &[&Path],
     glob_files: &[&str],
-) -> Result<BTreeSet<String>> {
+    ignore_missing: bool,
+) -> Result<Option<BTreeSet<String>>> {
     let mut include_names = BTreeSet::new();
+    let mut files_read = 0;
 
     for path in files {
+        if ignore_missing && !path.exists() {
+            continue;
+        }
+
         let new_names = read_resource_names_file(path)?;
         include_names.extend(new_names);
+        files_read += 1;
     }
 
     for pattern in glob_files {
@@ -47,9 +60,10 @@ pub fn resolve_resource_names_from_files(
 
         for entry in glob::glob(pattern)? {
             new_names.extend(read_resource_names_file(&entry?)?);
+            files_read += 1;
         }
 
-        if new_names.is_empty() {
+        if new_names.is_empty() && !ignore_missing {
             return Err(anyhow!(
                 "glob filter resolves to empty set; are you sure the glob pattern is correct?"
             ));
@@ -58,7 +72,11 @@ pub fn resolve_resource_names_from_files(
         include_names.extend(new_names);
     }
 
-    Ok(include_names)
+    if ignore_missing && files_read == 0 {
+        Ok(None)
+    } else {
+        Ok(Some(include_names))
+    }
 }
 
 pub fn filter_btreemap<V>(
@@ -75,3 +93,40 @@ pub fn filter_btreemap<V>(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_resource_names_ignore_missing() -> Result<()> {
+        let temp_dir = tempdir::TempDir::new("pyoxidizer-test")?;
+
+        let present_path = temp_dir.path().join("present");
+        std::fs::write(&present_path, "foo\n# comment\nbar\n")?;
+        let missing_path = temp_dir.path().join("missing");
+        let present = present_path.as_path();
+        let missing = missing_path.as_path();
+
+        assert!(resolve_resource_names_from_files(&[missing], &[], false).is_err());
+        assert_eq!(
+            resolve_resource_names_from_files(&[missing], &[], true)?,
+            None
+        );
+
+        let names = resolve_resource_names_from_files(&[present, missing], &[], true)?.unwrap();
+        assert_eq!(names.len(), 2);
+        assert!(names.contains("foo"));
+        assert!(names.contains("bar"));
+
+        let pattern = temp_dir.path().join("nomatch*").display().to_string();
+        let pattern = pattern.as_str();
+        assert!(resolve_resource_names_from_files(&[], &[pattern], false).is_err());
+        assert_eq!(
+            resolve_resource_names_from_files(&[], &[pattern], true)?,
+            None
+        );
+
+        Ok(())
+    }
+}
@@ -12,7 +12,9 @@ use std::fs::File;
 use std::io::Write;
 use std::path::{Path, PathBuf};
 
-use super::config::{EmbeddedPythonConfig, RawAllocator, RunMode, TerminfoResolution};
+use super::config::{
+    CoerceCLocale, EmbeddedPythonConfig, RawAllocator, RunMode, TerminfoResolution,
+};
 
 /// Obtain the Rust source code to construct a PythonConfig instance.
 pub fn derive_python_config(
@@ -28,10 +30,14 @@ pub fn derive_python_config(
          filesystem_importer: {},\n    \
          sys_paths: [{}].to_vec(),\n    \
          bytes_warning: {},\n    \
+         coerce_c_locale: {},\n    \
+         coerce_c_locale_warn: {},\n    \
+         development_mode: {},\n    \
          import_site: {},\n    \
          import_user_site: {},\n    \
          ignore_python_env: {},\n    \
          inspect: {},\n    \
+         int_max_str_digits: {},\n    \
          interactive: {},\n    \
          isolated: {},\n    \
          legacy_windows_fs_encoding: {},\n    \
@@ -42,6 +48,7 @@ pub fn derive_python_config(
          quiet: {},\n    \
          use_hash_seed: {},\n    \
          verbose: {},\n    \
+         warn_default_encoding: {},\n    \
          packed_resources: include_bytes!(r#\"{}\"#),\n    \
          extra_extension_modules: vec![],\n    \
          argvb: false,\n    \
@@ -50,6 +57,7 @@ pub fn derive_python_config(
          raw_allocator: {},\n    \
          terminfo_resolution: {},\n    \
          write_modules_directory_env: {},\n    \
+         write_modules_directory: {},\n    \
          run: {},\n\
          }}",
         match &embedded.stdio_encoding_name {
@@ -69,10 +77,21 @@ pub fn derive_python_config(
             .collect::<Vec<String>>()
             .join(", "),
         embedded.bytes_warning,
+        match embedded.coerce_c_locale {
+            Some(CoerceCLocale::Off) => "Some(false)",
+            Some(CoerceCLocale::On) | Some(CoerceCLocale::Warn) => "Some(true)",
+            None => "None",
+        },
+        embedded.coerce_c_locale == Some(CoerceCLocale::Warn),
+        embedded.development_mode,
         embedded.site_import,
         embedded.user_site_directory,
         embedded.ignore_environment,
         embedded.inspect,
+        match embedded.int_max_str_digits {
+            Some(value) => format!("Some({})", value),
+            None => "None".to_owned(),
+        },
         embedded.interactive,
         embedded.isolated,
         embedded.legacy_windows_fs_encoding,
@@ -83,11 +102,13 @@ pub fn derive_python_config(
         embedded.quiet,
         embedded.use_hash_seed,
         embedded.verbose,
+        embedded.warn_default_encoding,
         embedded_resources_path.display(),
         embedded.sys_frozen,
         embedded.sys_meipass,
         match embedded.raw_allocator {
             RawAllocator::Jemalloc => "pyembed::PythonRawAllocator::jemalloc()",
+            RawAllocator::Mimalloc => "pyembed::PythonRawAllocator::mimalloc()",
             RawAllocator::Rust => "pyembed::PythonRawAllocator::rust()",
             RawAllocator::System => "pyembed::PythonRawAllocator::system()",
         },
@@ -95,35 +116,55 @@ pub fn derive_python_config(
             TerminfoResolution::Dynamic => "pyembed::TerminfoResolution::Dynamic".to_string(),
             TerminfoResolution::None => "pyembed::TerminfoResolution::None".to_string(),
             TerminfoResolution::Static(ref v) => {
-                format!("pyembed::TerminfoResolution::Static(r###\"{}\"###", v)
+                format!(
+                    "pyembed::TerminfoResolution::Static(r###\"{}\"###.to_string())",
+                    v
+                )
+            }
+            TerminfoResolution::SystemPaths => {
+                "pyembed::TerminfoResolution::SystemPaths".to_string()
             }
         },
         match &embedded.write_modules_directory_env {
             Some(path) => "Some(\"".to_owned() + &path + "\".to_string())",
             _ => "None".to_owned(),
         },
-        match embedded.run_mode {
-            RunMode::Noop => "pyembed::PythonRunMode::None".to_owned(),
-            RunMode::Repl => "pyembed::PythonRunMode::Repl".to_owned(),
-            RunMode::Module { ref module } => {
-                "pyembed::PythonRunMode::Module { module: \"".to_owned()
-                    + module
-                    + "\".to_string() }"
-            }
-            RunMode::Eval { ref code } => {
-                "pyembed::PythonRunMode::Eval { code: r###\"".to_owned()
-                    + code
-                    + "\"###.to_string() }"
-            }
-            RunMode::File { ref path } => {
-                "pyembed::PythonRunMode::File { path: std::path::PathBuf::new(r###\"".to_owned()
-                    + path
-                    + "\"###) }"
-            }
+        match &embedded.write_modules_directory {
+            Some(path) => format!("Some(std::path::PathBuf::from(r###\"{}\"###))", path),
+            None => "None".to_owned(),
         },
+        derive_run_mode(&embedded.run_mode),
     )
 }
 
+/// Obtain the Rust source code to construct a PythonRunMode instance.
+fn derive_run_mode(run_mode: &RunMode) -> String {
+    match run_mode {
+        RunMode::Noop => "pyembed::PythonRunMode::None".to_owned(),
+        RunMode::Repl => "pyembed::PythonRunMode::Repl".to_owned(),
+        RunMode::Module { ref module } => {
+            "pyembed::PythonRunMode::Module { module: \"".to_owned() + module + "\".to_string() }"
+        }
+        RunMode::Eval { ref code } => {
+            "pyembed::PythonRunMode::Eval { code: r###\"".to_owned() + code + "\"###.to_string() }"
+        }
+        RunMode::File { ref path } => {
+            "pyembed::PythonRunMode::File { path: std::path::PathBuf::from(r###\"".to_owned()
+                + path
+                + "\"###) }"
+        }
+        RunMode::Sequence { ref steps } => {
+            "pyembed::PythonRunMode::Sequence { steps: vec![".to_owned()
+                + &steps
+                    .iter()
+                    .map(derive_run_mode)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+                + "] }"
+        }
+    }
+}
+
 /// Write a standalone .rs file containing a function for obtaining the default PythonConfig.
 pub fn write_default_python_config_rs(path: &Path, python_config_rs: &str) -> Result<()> {
     let mut f = File::create(&path)?;
@@ -148,3 +189,35 @@ pub fn write_default_python_config_rs(path: &Path, python_config_rs: &str) -> Re
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_derive_run_mode_file() {
+        assert_eq!(
+            derive_run_mode(&RunMode::File {
+                path: "$ORIGIN/main.py".to_string()
+            }),
+            "pyembed::PythonRunMode::File { path: std::path::PathBuf::from(r###\"$ORIGIN/main.py\"###) }"
+        );
+    }
+
+    #[test]
+    fn test_derive_run_mode_sequence() {
+        assert_eq!(
+            derive_run_mode(&RunMode::Sequence {
+                steps: vec![
+                    RunMode::File {
+                        path: "setup.py".to_string()
+                    },
+                    RunMode::Repl,
+                ]
+            }),
+            "pyembed::PythonRunMode::Sequence { steps: vec![\
+             pyembed::PythonRunMode::File { path: std::path::PathBuf::from(r###\"setup.py\"###) }, \
+             pyembed::PythonRunMode::Repl] }"
+        );
+    }
+}
@@ -13,7 +13,7 @@ use {
     super::distribution::{
         is_stdlib_test_package, resolve_python_distribution_from_location, BinaryLibpythonLinkMode,
         DistributionExtractLock, ExtensionModuleFilter, PythonDistribution,
-        PythonDistributionLocation,
+        PythonDistributionLocation, StdlibModuleFilter,
     },
     super::distutils::prepare_hacked_distutils,
     super::embedded_resource::{EmbeddedPythonResources, PrePackagedResources},
@@ -32,7 +32,7 @@ use {
         PythonModuleBytecodeFromSource, PythonModuleSource, PythonPackageDistributionResource,
         PythonPackageResource, PythonResource,
     },
-    python_packaging::resource_collection::PythonResourcesPolicy,
+    python_packaging::resource_collection::{PythonResourcesPolicy, ResourceDataKind},
     serde::{Deserialize, Serialize},
     slog::{info, warn},
     std::collections::{BTreeMap, BTreeSet, HashMap},
@@ -322,20 +322,19 @@ pub fn invoke_python(python_paths: &PythonPaths, logger: &slog::Logger, args: &[
 
 pub fn choose_variant<S: BuildHasher>(
     extensions: &[DistributionExtensionModule],
-    variants: &Option<HashMap<String, String, S>>,
+    variants: &Option<HashMap<String, Vec<String>, S>>,
 ) -> DistributionExtensionModule {
     if let Some(variants) = variants {
         if let Some(preferred) = variants.get(&extensions[0].module) {
-            let mut desired = extensions[0].clone();
-
-            for em in extensions {
-                if &em.variant == preferred {
-                    desired = em.clone();
-                    break;
+            // Preferences are tried in order. If none are available, fall back
+            // to the default variant.
+            for variant in preferred {
+                if let Some(em) = extensions.iter().find(|em| &em.variant == variant) {
+                    return em.clone();
                 }
             }
 
-            desired
+            extensions[0].clone()
         } else {
             extensions[0].clone()
         }
@@ -1061,10 +1060,11 @@ impl PythonDistribution for StandaloneDistribution {
         resources_policy: &PythonResourcesPolicy,
         config: &EmbeddedPythonConfig,
         extension_module_filter: &ExtensionModuleFilter,
-        preferred_extension_module_variants: Option<HashMap<String, String>>,
+        preferred_extension_module_variants: Option<HashMap<String, Vec<String>>>,
         include_sources: bool,
         include_resources: bool,
         include_test: bool,
+        stdlib_filter: &StdlibModuleFilter,
     ) -> Result<Box<dyn PythonBinaryBuilder>> {
         let python_exe = self.python_exe.clone();
 
@@ -1135,14 +1135,18 @@ impl PythonDistribution for StandaloneDistribution {
             include_sources,
             include_resources,
             include_test,
+            stdlib_filter,
         )?;
 
         // Always ensure minimal extension modules are present, otherwise we get
         // missing symbol errors at link time.
         if self.link_mode == StandaloneDistributionLinkMode::Static {
-            for ext in
-                self.filter_extension_modules(&logger, &ExtensionModuleFilter::Minimal, None)?
-            {
+            for ext in self.filter_extension_modules(
+                &logger,
+                &ExtensionModuleFilter::Minimal,
+                None,
+                &StdlibModuleFilter::default(),
+            )? {
                 builder
                     .resources
                     .add_builtin_distribution_extension_module(&ext)?;
@@ -1157,7 +1161,8 @@ impl PythonDistribution for StandaloneDistribution {
         &self,
         logger: &slog::Logger,
         filter: &ExtensionModuleFilter,
-        variants: Option<HashMap<String, String>>,
+        variants: Option<HashMap<String, Vec<String>>>,
+        stdlib_filter: &StdlibModuleFilter,
     ) -> Result<Vec<DistributionExtensionModule>> {
         let mut res = Vec::new();
 
@@ -1173,6 +1178,18 @@ impl PythonDistribution for StandaloneDistribution {
                 continue;
             }
 
+            if !stdlib_filter.matches(name)
+                && !ext_variants
+                    .iter()
+                    .any(|em| em.builtin_default || em.required)
+            {
+                info!(
+                    logger,
+                    "ignoring extension module {} because it is excluded by stdlib filter", name
+                );
+                continue;
+            }
+
             match filter {
                 ExtensionModuleFilter::Minimal => {
                     let ext_variants = ext_variants
@@ -1405,7 +1422,7 @@ pub struct StandalonePythonExecutableBuilder {
     extension_module_filter: ExtensionModuleFilter,
 
     /// Preferred extension module variants.
-    extension_module_variants: Option<HashMap<String, String>>,
+    extension_module_variants: Option<HashMap<String, Vec<String>>>,
 }
 
 impl StandalonePythonExecutableBuilder {
@@ -1431,11 +1448,13 @@ impl StandalonePythonExecutableBuilder {
         include_sources: bool,
         include_resources: bool,
         include_test: bool,
+        stdlib_filter: &StdlibModuleFilter,
     ) -> Result<()> {
         for ext in self.distribution.filter_extension_modules(
             logger,
             extension_module_filter,
             self.extension_module_variants.clone(),
+            stdlib_filter,
         )? {
             self.add_distribution_extension_module(&ext)?;
         }
@@ -1444,6 +1463,9 @@ impl StandalonePythonExecutableBuilder {
             if !include_test && is_stdlib_test_package(&source.package()) {
                 continue;
             }
+            if !stdlib_filter.matches(&source.name) {
+                continue;
+            }
 
             if include_sources {
                 self.add_module_source(&source)?;
@@ -1457,6 +1479,9 @@ impl StandalonePythonExecutableBuilder {
                 if !include_test && is_stdlib_test_package(&resource.leaf_package) {
                     continue;
                 }
+                if !stdlib_filter.matches(&resource.leaf_package) {
+                    continue;
+                }
 
                 self.add_package_resource(&resource)?;
             }
@@ -1551,6 +1576,10 @@ impl PythonBinaryBuilder for StandalonePythonExecutableBuilder {
         &self.resources_policy
     }
 
+    fn cache_tag(&self) -> &str {
+        &self.distribution.cache_tag
+    }
+
     fn python_exe_path(&self) -> &Path {
         &self.python_exe
     }
@@ -1894,15 +1923,21 @@ impl PythonBinaryBuilder for StandalonePythonExecutableBuilder {
         logger: &slog::Logger,
         files: &[&Path],
         glob_patterns: &[&str],
+        kinds: Option<&[ResourceDataKind]>,
+        ignore_missing: bool,
     ) -> Result<()> {
         self.resources
-            .filter_from_files(logger, files, glob_patterns)
+            .filter_from_files(logger, files, glob_patterns, kinds, ignore_missing)
     }
 
     fn requires_jemalloc(&self) -> bool {
         self.config.raw_allocator == RawAllocator::Jemalloc
     }
 
+    fn requires_mimalloc(&self) -> bool {
+        self.config.raw_allocator == RawAllocator::Mimalloc
+    }
+
     fn as_embedded_python_binary_data(
         &self,
         logger: &slog::Logger,
@@ -1940,7 +1975,7 @@ impl PythonBinaryBuilder for StandalonePythonExecutableBuilder {
 pub mod tests {
     use {
         super::*, crate::py_packaging::standalone_distribution::ExtensionModuleFilter,
-        crate::testutil::*,
+        crate::testutil::*, std::collections::hash_map::RandomState,
     };
 
     pub fn get_standalone_executable_builder(
@@ -1964,9 +1999,12 @@ pub mod tests {
 
         // We need to add minimal extension modules so builds actually work. If they are missing,
         // we'll get missing symbol errors during linking.
-        for ext in
-            distribution.filter_extension_modules(logger, &ExtensionModuleFilter::Minimal, None)?
-        {
+        for ext in distribution.filter_extension_modules(
+            logger,
+            &ExtensionModuleFilter::Minimal,
+            None,
+            &StdlibModuleFilter::default(),
+        )? {
             resources.add_builtin_distribution_extension_module(&ext)?;
         }
 
@@ -2004,4 +2042,64 @@ pub mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_filter_extension_modules_stdlib_filter() -> Result<()> {
+        let logger = get_logger()?;
+        let distribution = get_default_distribution()?;
+
+        let filter = StdlibModuleFilter::new(&[], &["_*".to_string()])?;
+        let res = distribution.filter_extension_modules(
+            &logger,
+            &ExtensionModuleFilter::All,
+            None,
+            &filter,
+        )?;
+
+        for em in &res {
+            if em.module.starts_with('_') {
+                assert!(em.builtin_default || em.required, "{}", em.module);
+            }
+        }
+        assert!(!res.iter().any(|em| em.module == "_sqlite3"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_choose_variant() {
+        let default = DistributionExtensionModule {
+            module: "foo".to_string(),
+            init_fn: Some("PyInit_foo".to_string()),
+            builtin_default: false,
+            disableable: true,
+            object_paths: vec![],
+            static_library: None,
+            shared_library: None,
+            links: vec![],
+            required: false,
+            variant: "default".to_string(),
+            licenses: None,
+            license_paths: None,
+            license_public_domain: None,
+        };
+        let other = DistributionExtensionModule {
+            variant: "other".to_string(),
+            ..default.clone()
+        };
+        let extensions = vec![default.clone(), other.clone()];
+
+        assert_eq!(choose_variant::<RandomState>(&extensions, &None), default);
+
+        let mut variants = HashMap::new();
+        variants.insert(
+            "foo".to_string(),
+            vec!["missing".to_string(), "other".to_string()],
+        );
+        assert_eq!(choose_variant(&extensions, &Some(variants)), other);
+
+        let mut variants = HashMap::new();
+        variants.insert("foo".to_string(), vec!["missing".to_string()]);
+        assert_eq!(choose_variant(&extensions, &Some(variants)), default);
+    }
 }
@@ -8,6 +8,7 @@ use {
     crate::py_packaging::distribution::{
         DistributionFlavor, PythonDistributionLocation, PythonDistributionRecord,
     },
+    anyhow::{anyhow, Result},
     lazy_static::lazy_static,
 };
 
@@ -23,34 +24,68 @@ pub struct PythonDistributionCollection {
 
 impl PythonDistributionCollection {
     /// Find a Python distribution given a target triple and flavor preference.
+    ///
+    /// If `python_major_minor_version` is defined, only distributions of that
+    /// Python version (e.g. `3.8`) are considered. The `Standalone` flavor
+    /// prefers dynamically linked distributions and falls back to statically
+    /// linked ones.
+    ///
+    /// An error is returned if no distribution matches or if multiple
+    /// distributions are equally good matches.
     pub fn find_distribution(
         &self,
         target_triple: &str,
         flavor: &DistributionFlavor,
-    ) -> Option<PythonDistributionRecord> {
-        for dist in &self.dists {
-            if dist.target_triple != target_triple {
-                continue;
-            }
+        python_major_minor_version: Option<&str>,
+    ) -> Result<PythonDistributionRecord> {
+        let prebuilt_extension_module_preference: &[bool] = match flavor {
+            DistributionFlavor::Standalone => &[true, false],
+            DistributionFlavor::StandaloneStatic => &[false],
+            DistributionFlavor::StandaloneDynamic => &[true],
+        };
 
-            match flavor {
-                DistributionFlavor::Standalone => {
-                    return Some(dist.clone());
-                }
-                DistributionFlavor::StandaloneStatic => {
-                    if !dist.supports_prebuilt_extension_modules {
-                        return Some(dist.clone());
-                    }
-                }
-                DistributionFlavor::StandaloneDynamic => {
-                    if dist.supports_prebuilt_extension_modules {
-                        return Some(dist.clone());
-                    }
+        for supports_prebuilt in prebuilt_extension_module_preference {
+            let matches = self
+                .dists
+                .iter()
+                .filter(|dist| {
+                    dist.target_triple == target_triple
+                        && dist.supports_prebuilt_extension_modules == *supports_prebuilt
+                        && python_major_minor_version
+                            .map_or(true, |version| dist.python_major_minor_version == version)
+                })
+                .collect::<Vec<_>>();
+
+            match matches.as_slice() {
+                [] => continue,
+                [dist] => return Ok((*dist).clone()),
+                _ => {
+                    return Err(match python_major_minor_version {
+                        Some(version) => anyhow!(
+                            "multiple default Python {} distributions match {}",
+                            version,
+                            target_triple
+                        ),
+                        None => anyhow!(
+                        "multiple default Python distributions match {}; specify a Python version",
+                        target_triple
+                    ),
+                    })
                 }
             }
         }
 
-        None
+        Err(match python_major_minor_version {
+            Some(version) => anyhow!(
+                "could not find default Python {} distribution for {}",
+                version,
+                target_triple
+            ),
+            None => anyhow!(
+                "could not find default Python distribution for {}",
+                target_triple
+            ),
+        })
     }
 }
 
@@ -62,8 +97,10 @@ lazy_static! {
                 location: PythonDistributionLocation::Url {
                     url: "https://github.com/indygreg/python-build-standalone/releases/download/20200517/cpython-3.8.3-x86_64-unknown-linux-gnu-pgo-20200518T0040.tar.zst".to_string(),
                     sha256: "a9aee0f0bd2f8aab09b386915daea508e6713ad43a45fa13afe43fd3e1b1fd9b".to_string(),
+                    mirror_urls: vec![],
                 },
                 target_triple: "x86_64-unknown-linux-gnu".to_string(),
+                python_major_minor_version: "3.8".to_string(),
                 supports_prebuilt_extension_modules: true,
             },
 
@@ -72,8 +109,10 @@ lazy_static! {
                 location: PythonDistributionLocation::Url {
                     url: "https://github.com/indygreg/python-build-standalone/releases/download/20200517/cpython-3.8.3-x86_64-unknown-linux-musl-noopt-20200518T0040.tar.zst".to_string(),
                     sha256: "0feb2e51b65a9608b4687d6d37ec1ddf3cda26408655de65306f25121eace6c0".to_string(),
+                    mirror_urls: vec![],
                 },
                 target_triple: "x86_64-unknown-linux-musl".to_string(),
+                python_major_minor_version: "3.8".to_string(),
                 supports_prebuilt_extension_modules: false,
             },
 
@@ -89,16 +128,20 @@ lazy_static! {
                 location: PythonDistributionLocation::Url {
                     url: "https://github.com/indygreg/python-build-standalone/releases/download/20200517/cpython-3.8.3-i686-pc-windows-msvc-shared-pgo-20200518T0154.tar.zst".to_string(),
                     sha256: "5293cc4f247ac26f4a4be101cc7562e53a43896a33ed464cd0bd31ef760a89d9".to_string(),
+                    mirror_urls: vec![],
                 },
                 target_triple: "i686-pc-windows-msvc".to_string(),
+                python_major_minor_version: "3.8".to_string(),
                 supports_prebuilt_extension_modules: true,
             },
             PythonDistributionRecord {
                 location: PythonDistributionLocation::Url {
                     url: "https://github.com/indygreg/python-build-standalone/releases/download/20200517/cpython-3.8.3-x86_64-pc-windows-msvc-shared-pgo-20200517T2207.tar.zst".to_string(),
                     sha256: "da40fadb58d91358c05093220fad201a42ceac320244667b00715d0cd57208c2".to_string(),
+                    mirror_urls: vec![],
                 },
                 target_triple: "x86_64-pc-windows-msvc".to_string(),
+                python_major_minor_version: "3.8".to_string(),
                 supports_prebuilt_extension_modules: true,
             },
 
@@ -107,16 +150,20 @@ lazy_static! {
                 location: PythonDistributionLocation::Url {
                     url: "https://github.com/indygreg/python-build-standalone/releases/download/20200517/cpython-3.8.3-i686-pc-windows-msvc-static-noopt-20200517T2247.tar.zst".to_string(),
                     sha256: "2b7857e66d00068e407a82e737d19156ec24e9c6808b71170244e8707b3e8bed".to_string(),
+                    mirror_urls: vec![],
                 },
                 target_triple: "i686-pc-windows-msvc".to_string(),
+                python_major_minor_version: "3.8".to_string(),
                 supports_prebuilt_extension_modules: false,
             },
             PythonDistributionRecord {
                 location: PythonDistributionLocation::Url {
                     url: "https://github.com/indygreg/python-build-standalone/releases/download/20200517/cpython-3.8.3-x86_64-pc-windows-msvc-static-noopt-20200517T2203.tar.zst".to_string(),
                     sha256: "a5357691aafb186c65e7736e9c21a4ba47cb675a25d89ac65be320698f72fd9e".to_string(),
+                    mirror_urls: vec![],
                 },
                 target_triple: "x86_64-pc-windows-msvc".to_string(),
+                python_major_minor_version: "3.8".to_string(),
                 supports_prebuilt_extension_modules: false,
             },
 
@@ -125,8 +172,10 @@ lazy_static! {
                 location: PythonDistributionLocation::Url {
                     url: "https://github.com/indygreg/python-build-standalone/releases/download/20200530/cpython-3.8.3-x86_64-apple-darwin-pgo-20200530T1845.tar.zst".to_string(),
                     sha256: "adf98af0f0ba8f55a84476e0800210b59edd67bb98800be3ebc5d1f0157ff01e".to_string(),
+                    mirror_urls: vec![],
                 },
                 target_triple: "x86_64-apple-darwin".to_string(),
+                python_major_minor_version: "3.8".to_string(),
                 supports_prebuilt_extension_modules: true,
             },
         ];
@@ -144,3 +193,97 @@ lazy_static! {
         }
     };
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(
+        version: &str,
+        supports_prebuilt_extension_modules: bool,
+    ) -> PythonDistributionRecord {
+        PythonDistributionRecord {
+            location: PythonDistributionLocation::Url {
+                url: format!("https://example.com/cpython-{}.tar.zst", version),
+                sha256: "0".repeat(64),
+                mirror_urls: vec![],
+            },
+            target_triple: "x86_64-unknown-linux-gnu".to_string(),
+            python_major_minor_version: version.to_string(),
+            supports_prebuilt_extension_modules,
+        }
+    }
+
+    #[test]
+    fn test_find_distribution_python_version() {
+        let dists = PythonDistributionCollection {
+            dists: vec![record("3.8", true), record("3.9", true)],
+        };
+
+        let dist = dists
+            .find_distribution(
+                "x86_64-unknown-linux-gnu",
+                &DistributionFlavor::Standalone,
+                Some("3.9"),
+            )
+            .unwrap();
+        assert_eq!(dist.python_major_minor_version, "3.9");
+
+        let err = dists
+            .find_distribution(
+                "x86_64-unknown-linux-gnu",
+                &DistributionFlavor::Standalone,
+                None,
+            )
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "multiple default Python distributions match x86_64-unknown-linux-gnu; specify a Python version"
+        );
+    }
+
+    #[test]
+    fn test_find_distribution_ambiguous() {
+        let dists = PythonDistributionCollection {
+            dists: vec![record("3.8", true), record("3.8", true)],
+        };
+
+        let err = dists
+            .find_distribution(
+                "x86_64-unknown-linux-gnu",
+                &DistributionFlavor::Standalone,
+                Some("3.8"),
+            )
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "multiple default Python 3.8 distributions match x86_64-unknown-linux-gnu"
+        );
+    }
+
+    #[test]
+    fn test_find_distribution_flavor() {
+        let dists = PythonDistributionCollection {
+            dists: vec![record("3.8", false), record("3.8", true)],
+        };
+
+        // Standalone prefers the dynamic distribution rather than being ambiguous.
+        let dist = dists
+            .find_distribution(
+                "x86_64-unknown-linux-gnu",
+                &DistributionFlavor::Standalone,
+                None,
+            )
+            .unwrap();
+        assert!(dist.supports_prebuilt_extension_modules);
+
+        let dist = dists
+            .find_distribution(
+                "x86_64-unknown-linux-gnu",
+                &DistributionFlavor::StandaloneStatic,
+                None,
+            )
+            .unwrap();
+        assert!(!dist.supports_prebuilt_extension_modules);
+    }
+}
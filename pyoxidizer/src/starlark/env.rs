@@ -144,10 +144,17 @@ impl EnvironmentContext {
     }
 
     pub fn set_build_path(&mut self, path: &Path) -> Result<()> {
+        let path = path
+            .to_str()
+            .ok_or_else(|| anyhow!("build path is not valid UTF-8"))?;
+        let path = PathBuf::from(
+            expand_path_vars(path, &self.cwd.display().to_string()).or_else(|e| Err(anyhow!(e)))?,
+        );
+
         let path = if path.is_relative() {
             self.cwd.join(path)
         } else {
-            path.to_path_buf()
+            path
         }
         .parse_dot()?;
 
@@ -478,6 +485,70 @@ fn starlark_resolve_targets(env: &Environment, call_stack: &Vec<(String, String)
     Ok(Value::new(None))
 }
 
+/// Expand variable references in a path string.
+///
+/// `$ORIGIN` expands to `origin`, which should be the directory containing
+/// the config file. `$HOME`, `$CARGO_MANIFEST_DIR`, and `${VAR}` expand to
+/// the value of the named environment variable. `$HOME` falls back to
+/// `USERPROFILE` so it works on Windows. `$CARGO_TARGET_DIR` falls back to
+/// Cargo's default of `target` under `origin`. Referencing an undefined
+/// environment variable is an error. Other `$NAME` sequences are left as-is.
+pub(crate) fn expand_path_vars(input: &str, origin: &str) -> Result<String, String> {
+    let lookup = |name: &str| {
+        std::env::var(name).or_else(|_| {
+            Err(format!(
+                "environment variable {} referenced by {} is not defined",
+                name, input
+            ))
+        })
+    };
+
+    let mut res = String::with_capacity(input.len());
+    let mut rest = input;
+
+    while let Some(pos) = rest.find('$') {
+        res.push_str(&rest[..pos]);
+        rest = &rest[pos + 1..];
+
+        let (name, braced) = if rest.starts_with('{') {
+            let inner = &rest[1..];
+            let end = inner
+                .find('}')
+                .ok_or_else(|| format!("unterminated variable reference in {}", input))?;
+            rest = &inner[end + 1..];
+            (&inner[..end], true)
+        } else {
+            let end = rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len());
+            let name = &rest[..end];
+            rest = &rest[end..];
+            (name, false)
+        };
+
+        match name {
+            "ORIGIN" => res.push_str(origin),
+            "HOME" => {
+                res.push_str(&lookup(name).or_else(|e| std::env::var("USERPROFILE").or(Err(e)))?)
+            }
+            "CARGO_MANIFEST_DIR" => res.push_str(&lookup(name)?),
+            "CARGO_TARGET_DIR" => res.push_str(
+                &lookup(name)
+                    .unwrap_or_else(|_| Path::new(origin).join("target").display().to_string()),
+            ),
+            _ if braced => res.push_str(&lookup(name)?),
+            _ => {
+                res.push('$');
+                res.push_str(name);
+            }
+        }
+    }
+
+    res.push_str(rest);
+
+    Ok(res)
+}
+
 /// set_build_path(path)
 fn starlark_set_build_path(env: &Environment, path: &Value) -> ValueResult {
     let path = required_str_arg("path", &path)?;
@@ -575,6 +646,88 @@ pub mod tests {
         assert_eq!(target.to_str(), crate::project_building::HOST);
     }
 
+    #[test]
+    fn test_expand_path_vars() {
+        std::env::set_var("PYOXIDIZER_TEST_EXPAND_PATH_VARS", "/from-env");
+
+        assert_eq!(
+            expand_path_vars("$ORIGIN/build", "/project"),
+            Ok("/project/build".to_string())
+        );
+        assert_eq!(
+            expand_path_vars("${PYOXIDIZER_TEST_EXPAND_PATH_VARS}/build", "/project"),
+            Ok("/from-env/build".to_string())
+        );
+        assert_eq!(
+            expand_path_vars("build/$OTHER", "/project"),
+            Ok("build/$OTHER".to_string())
+        );
+    }
+
+    #[test]
+    fn test_expand_path_vars_undefined() {
+        std::env::remove_var("PYOXIDIZER_TEST_UNDEFINED_VAR");
+
+        assert_eq!(
+            expand_path_vars("${PYOXIDIZER_TEST_UNDEFINED_VAR}/build", "/project"),
+            Err(
+                "environment variable PYOXIDIZER_TEST_UNDEFINED_VAR referenced by \
+                 ${PYOXIDIZER_TEST_UNDEFINED_VAR}/build is not defined"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_expand_path_vars_unterminated() {
+        assert_eq!(
+            expand_path_vars("${ORIGIN/build", "/project"),
+            Err("unterminated variable reference in ${ORIGIN/build".to_string())
+        );
+    }
+
+    #[test]
+    fn test_set_build_path_origin() {
+        let mut env = starlark_env();
+        starlark_eval_in_env(&mut env, "set_build_path('$ORIGIN/custom')").unwrap();
+
+        let context = env.get("CONTEXT").unwrap();
+
+        context.downcast_apply(|x: &EnvironmentContext| {
+            assert_eq!(x.build_path, x.cwd.join("custom"));
+        });
+    }
+
+    #[test]
+    fn test_expand_path_vars_cargo_target_dir() {
+        let origin = PathBuf::from("/project");
+        let default = origin.join("target").join("pyoxidizer");
+
+        std::env::remove_var("CARGO_TARGET_DIR");
+
+        assert_eq!(
+            expand_path_vars("$CARGO_TARGET_DIR/pyoxidizer", "/project"),
+            Ok(default.display().to_string())
+        );
+        assert_eq!(
+            expand_path_vars("${CARGO_TARGET_DIR}/pyoxidizer", "/project"),
+            Ok(default.display().to_string())
+        );
+        assert_eq!(
+            expand_path_vars("build/${CARGO_TARGET_DIR}", "/project"),
+            Ok(format!("build/{}", origin.join("target").display()))
+        );
+
+        std::env::set_var("CARGO_TARGET_DIR", "/cargo-target");
+
+        assert_eq!(
+            expand_path_vars("${CARGO_TARGET_DIR}/pyoxidizer", "/project"),
+            Ok("/cargo-target/pyoxidizer".to_string())
+        );
+
+        std::env::remove_var("CARGO_TARGET_DIR");
+    }
+
     #[test]
     fn test_register_target() {
         let mut env = starlark_env();
@@ -8,6 +8,7 @@ use {
     codemap::CodeMap,
     codemap_diagnostic::{Diagnostic, Level},
     starlark::environment::Environment,
+    std::collections::BTreeMap,
     std::path::Path,
     std::sync::{Arc, Mutex},
 };
@@ -112,3 +113,77 @@ pub fn eval_starlark_config_file(
     )
     .or_else(|d| Err(anyhow!(d.message)))
 }
+
+/// Evaluate a Starlark configuration file for multiple build targets.
+///
+/// The file is evaluated independently for each target triple. Failures
+/// evaluating one target do not prevent evaluation of others.
+///
+/// Returns a mapping of target triple to the result of evaluating the
+/// file for that target.
+pub fn eval_starlark_config_file_matrix(
+    logger: &slog::Logger,
+    path: &Path,
+    build_target_triples: &[&str],
+    release: bool,
+    verbose: bool,
+    resolve_targets: Option<Vec<String>>,
+    build_script_mode: bool,
+) -> BTreeMap<String, Result<EvalResult>> {
+    build_target_triples
+        .iter()
+        .map(|triple| {
+            (
+                triple.to_string(),
+                eval_starlark_config_file(
+                    logger,
+                    path,
+                    triple,
+                    release,
+                    verbose,
+                    resolve_targets.clone(),
+                    build_script_mode,
+                ),
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::testutil::*};
+
+    #[test]
+    fn test_eval_matrix() -> Result<()> {
+        let logger = get_logger()?;
+        let temp_dir = tempdir::TempDir::new("pyoxidizer-test")?;
+
+        let config_path = temp_dir.path().join("pyoxidizer.bzl");
+        std::fs::write(
+            &config_path,
+            "def resolve():\n    \
+                 known = {'good-triple': True}\n    \
+                 return known[BUILD_TARGET_TRIPLE]\n\
+             \n\
+             resolve()\n",
+        )?;
+
+        let res = eval_starlark_config_file_matrix(
+            &logger,
+            &config_path,
+            &["good-triple", "bad-triple"],
+            false,
+            false,
+            None,
+            false,
+        );
+
+        assert_eq!(res.len(), 2);
+
+        let good = res["good-triple"].as_ref().unwrap();
+        assert_eq!(good.context.build_target_triple, "good-triple");
+        assert!(res["bad-triple"].is_err());
+
+        Ok(())
+    }
+}
@@ -390,6 +390,36 @@ impl FileManifest {
         Ok(Value::new(None))
     }
 
+    /// FileManifest.remove_files(glob_files, prefix=None)
+    pub fn remove_files(&mut self, glob_files: &Value, prefix: &Value) -> ValueResult {
+        required_list_arg("glob_files", "string", glob_files)?;
+        let prefix = optional_str_arg("prefix", prefix)?;
+
+        let patterns = glob_files
+            .into_iter()?
+            .map(|x| match &prefix {
+                Some(prefix) => format!("{}/{}", prefix, x.to_string()),
+                None => x.to_string(),
+            })
+            .collect::<Vec<String>>();
+
+        let removed = self.manifest.remove_files(&patterns).or_else(|e| {
+            Err(RuntimeError {
+                code: "PYOXIDIZER_BUILD",
+                message: e.to_string(),
+                label: "remove_files()".to_string(),
+            }
+            .into())
+        })?;
+
+        Ok(Value::from(
+            removed
+                .iter()
+                .map(|p| Value::from(p.display().to_string()))
+                .collect::<Vec<Value>>(),
+        ))
+    }
+
     /// FileManifest.install(path, replace=true)
     pub fn install(&self, env: &Environment, path: &Value, replace: &Value) -> ValueResult {
         let path = required_str_arg("path", &path)?;
@@ -544,6 +574,13 @@ starlark_module! { file_resource_env =>
         })
     }
 
+    #[allow(clippy::ptr_arg)]
+    FileManifest.remove_files(this, glob_files, prefix=None) {
+        this.downcast_apply_mut(|manifest: &mut FileManifest| {
+            manifest.remove_files(&glob_files, &prefix)
+        })
+    }
+
     #[allow(clippy::ptr_arg)]
     FileManifest.install(env env, this, path, replace=true) {
         this.downcast_apply(|manifest: &FileManifest| {
@@ -683,6 +720,40 @@ mod tests {
         starlark_eval_in_env(&mut env, "m.add_python_resource('bin', exe)").unwrap();
     }
 
+    #[test]
+    fn test_remove_files() {
+        let mut manifest = RawFileManifest::default();
+        let content = RawFileContent {
+            data: vec![],
+            executable: false,
+        };
+
+        manifest
+            .add_file(&PathBuf::from("lib/foo.so"), &content)
+            .unwrap();
+        manifest
+            .add_file(&PathBuf::from("lib/foo.so.debug"), &content)
+            .unwrap();
+        manifest
+            .add_file(&PathBuf::from("foo.so.debug"), &content)
+            .unwrap();
+
+        let mut env = starlark_env();
+        env.set("m", Value::new(FileManifest { manifest })).unwrap();
+
+        let removed =
+            starlark_eval_in_env(&mut env, "m.remove_files(['*.so.debug'], prefix='lib')").unwrap();
+        assert_eq!(removed.get_type(), "list");
+        assert_eq!(removed.length().unwrap(), 1);
+
+        let m = env.get("m").unwrap();
+        m.downcast_apply(|m: &FileManifest| {
+            assert!(m.manifest.has_path(&PathBuf::from("lib/foo.so")));
+            assert!(!m.manifest.has_path(&PathBuf::from("lib/foo.so.debug")));
+            assert!(m.manifest.has_path(&PathBuf::from("foo.so.debug")));
+        });
+    }
+
     #[test]
     fn test_install() {
         let mut env = starlark_env();
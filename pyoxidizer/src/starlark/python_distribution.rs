@@ -10,14 +10,14 @@ use {
         PythonSourceModule,
     },
     super::util::{
-        optional_dict_arg, optional_str_arg, optional_type_arg, required_bool_arg, required_str_arg,
+        optional_list_arg, optional_str_arg, optional_type_arg, required_bool_arg, required_str_arg,
     },
     crate::py_packaging::config::EmbeddedPythonConfig,
     crate::py_packaging::distribution::BinaryLibpythonLinkMode,
     crate::py_packaging::distribution::{
         default_distribution_location, is_stdlib_test_package, resolve_distribution,
         DistributionFlavor, ExtensionModuleFilter, PythonDistribution as PythonDistributionTrait,
-        PythonDistributionLocation,
+        PythonDistributionLocation, StdlibModuleFilter,
     },
     anyhow::{anyhow, Result},
     itertools::Itertools,
@@ -139,16 +139,150 @@ impl TypedValue for PythonDistribution {
     }
 }
 
+/// Resolve a dict of preferred extension module variants.
+///
+/// Values can be a single variant name or a list of variant names in order
+/// of preference.
+fn resolve_preferred_variants(
+    arg_name: &str,
+    value: &Value,
+) -> Result<Option<HashMap<String, Vec<String>>>, ValueError> {
+    match value.get_type() {
+        "NoneType" => Ok(None),
+        "dict" => {
+            let mut m = HashMap::new();
+
+            for k in value.into_iter()? {
+                if k.get_type() != "string" {
+                    return Err(RuntimeError {
+                        code: INCORRECT_PARAMETER_TYPE_ERROR_CODE,
+                        message: format!(
+                            "dict {} expects keys of type string; got {}",
+                            arg_name,
+                            k.get_type()
+                        ),
+                        label: format!("expected type string; got {}", k.get_type()),
+                    }
+                    .into());
+                }
+
+                let v = value.at(k.clone())?;
+
+                let variants = match v.get_type() {
+                    "string" => vec![v.to_string()],
+                    "list" => {
+                        let mut variants = Vec::new();
+
+                        for x in v.into_iter()? {
+                            if x.get_type() != "string" {
+                                return Err(RuntimeError {
+                                    code: INCORRECT_PARAMETER_TYPE_ERROR_CODE,
+                                    message: format!(
+                                        "dict {} expects lists of string; got {}",
+                                        arg_name,
+                                        x.get_type()
+                                    ),
+                                    label: format!("expected type string; got {}", x.get_type()),
+                                }
+                                .into());
+                            }
+
+                            variants.push(x.to_string());
+                        }
+
+                        variants
+                    }
+                    t => {
+                        return Err(RuntimeError {
+                            code: INCORRECT_PARAMETER_TYPE_ERROR_CODE,
+                            message: format!(
+                                "dict {} expects values of type string or list; got {}",
+                                arg_name, t
+                            ),
+                            label: format!("expected type string or list; got {}", t),
+                        }
+                        .into());
+                    }
+                };
+
+                m.insert(k.to_string(), variants);
+            }
+
+            Ok(Some(m))
+        }
+        t => Err(RuntimeError {
+            code: INCORRECT_PARAMETER_TYPE_ERROR_CODE,
+            message: format!("function expects a dict for {}; got type {}", arg_name, t),
+            label: format!("expected type dict; got {}", t),
+        }
+        .into()),
+    }
+}
+
+/// Resolve a URL value that may reference an environment variable.
+///
+/// Values of the form `env:NAME` resolve to the value of environment variable
+/// `NAME`. Other values are returned as-is. `arg_name` is the argument holding
+/// the value and is included in error messages.
+fn resolve_env_url(arg_name: &str, value: &str) -> Result<String, ValueError> {
+    if value.starts_with("env:") {
+        let name = &value["env:".len()..];
+
+        std::env::var(name).or_else(|_| {
+            Err(RuntimeError {
+                code: "PYOXIDIZER_BUILD",
+                message: format!(
+                    "environment variable {} referenced by {} is not set",
+                    name, arg_name
+                ),
+                label: "PythonDistribution()".to_string(),
+            }
+            .into())
+        })
+    } else {
+        Ok(value.to_string())
+    }
+}
+
+/// Resolve a distribution URL and validate it is an http or https URL.
+///
+/// `requirement` describes the constraint and is used as the error message.
+fn resolve_distribution_url(
+    arg_name: &str,
+    value: &str,
+    requirement: &str,
+) -> Result<String, ValueError> {
+    let url = resolve_env_url(arg_name, value)?;
+
+    let valid = match url::Url::parse(&url) {
+        Ok(u) => u.scheme() == "http" || u.scheme() == "https",
+        Err(_) => false,
+    };
+
+    if valid {
+        Ok(url)
+    } else {
+        Err(RuntimeError {
+            code: INCORRECT_PARAMETER_TYPE_ERROR_CODE,
+            message: format!("{}; got {}", requirement, url),
+            label: "PythonDistribution()".to_string(),
+        }
+        .into())
+    }
+}
+
 // Starlark functions.
 impl PythonDistribution {
-    /// default_python_distribution(flavor, build_target=None)
+    /// default_python_distribution(flavor, build_target=None, python_version=None)
     fn default_python_distribution(
         env: &Environment,
         flavor: &Value,
         build_target: &Value,
+        python_version: &Value,
     ) -> ValueResult {
         let flavor = required_str_arg("flavor", flavor)?;
         let build_target = optional_str_arg("build_target", build_target)?;
+        let python_version = optional_str_arg("python_version", python_version)?;
 
         let build_target = match build_target {
             Some(t) => t,
@@ -169,7 +303,12 @@ impl PythonDistribution {
             }
         };
 
-        let location = default_distribution_location(&flavor, &build_target).or_else(|e| {
+        let location = default_distribution_location(
+            &flavor,
+            &build_target,
+            python_version.as_ref().map(|x| x.as_str()),
+        )
+        .or_else(|e| {
             Err(RuntimeError {
                 code: "PYOXIDIZER_BUILD",
                 message: e.to_string(),
@@ -194,11 +333,37 @@ impl PythonDistribution {
         local_path: &Value,
         url: &Value,
         flavor: &Value,
+        mirror_urls: &Value,
     ) -> ValueResult {
         required_str_arg("sha256", sha256)?;
         optional_str_arg("local_path", local_path)?;
         optional_str_arg("url", url)?;
         let flavor = required_str_arg("flavor", flavor)?;
+        optional_list_arg("mirror_urls", "string", mirror_urls)?;
+
+        let mirror_urls = match mirror_urls.get_type() {
+            "list" => mirror_urls
+                .into_iter()
+                .unwrap()
+                .map(|x| {
+                    resolve_distribution_url(
+                        "mirror_urls",
+                        &x.to_string(),
+                        "mirror_urls must be http or https URLs",
+                    )
+                })
+                .collect::<Result<Vec<_>, _>>()?,
+            _ => Vec::new(),
+        };
+
+        if local_path.get_type() != "NoneType" && !mirror_urls.is_empty() {
+            return Err(RuntimeError {
+                code: INCORRECT_PARAMETER_TYPE_ERROR_CODE,
+                message: "mirror_urls cannot be used with local_path".to_string(),
+                label: "PythonDistribution()".to_string(),
+            }
+            .into());
+        }
 
         if local_path.get_type() != "NoneType" && url.get_type() != "NoneType" {
             return Err(RuntimeError {
@@ -216,8 +381,13 @@ impl PythonDistribution {
             }
         } else {
             PythonDistributionLocation::Url {
-                url: url.to_string(),
+                url: resolve_distribution_url(
+                    "url",
+                    &url.to_string(),
+                    "url must be an http or https URL",
+                )?,
                 sha256: sha256.to_string(),
+                mirror_urls,
             }
         };
 
@@ -253,6 +423,8 @@ impl PythonDistribution {
     ///     include_sources=true,
     ///     include_resources=true,
     ///     include_test=false,
+    ///     stdlib_includes=None,
+    ///     stdlib_excludes=None,
     /// )
     #[allow(clippy::ptr_arg, clippy::too_many_arguments)]
     fn to_python_executable_starlark(
@@ -267,21 +439,43 @@ impl PythonDistribution {
         include_sources: &Value,
         include_resources: &Value,
         include_test: &Value,
+        stdlib_includes: &Value,
+        stdlib_excludes: &Value,
     ) -> ValueResult {
         let name = required_str_arg("name", &name)?;
         let resources_policy = required_str_arg("resources_policy", &resources_policy)?;
         optional_type_arg("config", "PythonInterpreterConfig", &config)?;
         let extension_module_filter =
             required_str_arg("extension_module_filter", &extension_module_filter)?;
-        optional_dict_arg(
+        let preferred_extension_module_variants = resolve_preferred_variants(
             "preferred_extension_module_variants",
-            "string",
-            "string",
             &preferred_extension_module_variants,
         )?;
         let include_sources = required_bool_arg("include_sources", &include_sources)?;
         let include_resources = required_bool_arg("include_resources", &include_resources)?;
         let include_test = required_bool_arg("include_test", &include_test)?;
+        optional_list_arg("stdlib_includes", "string", &stdlib_includes)?;
+        optional_list_arg("stdlib_excludes", "string", &stdlib_excludes)?;
+
+        let list_strings = |value: &Value| -> Vec<String> {
+            match value.get_type() {
+                "list" => value.into_iter().unwrap().map(|x| x.to_string()).collect(),
+                _ => Vec::new(),
+            }
+        };
+
+        let stdlib_filter = StdlibModuleFilter::new(
+            &list_strings(stdlib_includes),
+            &list_strings(stdlib_excludes),
+        )
+        .or_else(|e| {
+            Err(RuntimeError {
+                code: "PYOXIDIZER_BUILD",
+                message: e.to_string(),
+                label: "stdlib_includes/stdlib_excludes".to_string(),
+            }
+            .into())
+        })?;
 
         let context = env.get("CONTEXT").expect("CONTEXT not defined");
         let logger = context.downcast_apply(|x: &EnvironmentContext| x.logger.clone());
@@ -309,24 +503,6 @@ impl PythonDistribution {
                 .into())
             })?;
 
-        let preferred_extension_module_variants =
-            match preferred_extension_module_variants.get_type() {
-                "NoneType" => None,
-                "dict" => {
-                    let mut m = HashMap::new();
-
-                    for k in preferred_extension_module_variants.into_iter()? {
-                        let v = preferred_extension_module_variants
-                            .at(k.clone())?
-                            .to_string();
-                        m.insert(k.to_string(), v);
-                    }
-
-                    Some(m)
-                }
-                _ => panic!("type should have been validated above"),
-            };
-
         self.ensure_distribution_resolved(&logger).or_else(|e| {
             Err(RuntimeError {
                 code: "PYOXIDIZER_BUILD",
@@ -363,6 +539,7 @@ impl PythonDistribution {
                     include_sources,
                     include_resources,
                     include_test,
+                    &stdlib_filter,
                 )
                 .or_else(|e| {
                     Err(RuntimeError {
@@ -383,12 +560,8 @@ impl PythonDistribution {
         preferred_variants: &Value,
     ) -> ValueResult {
         let filter = required_str_arg("filter", &filter)?;
-        optional_dict_arg(
-            "preferred_variants",
-            "string",
-            "string",
-            &preferred_variants,
-        )?;
+        let preferred_variants =
+            resolve_preferred_variants("preferred_variants", &preferred_variants)?;
 
         let filter = ExtensionModuleFilter::try_from(filter.as_str()).or_else(|e| {
             Err(RuntimeError {
@@ -399,21 +572,6 @@ impl PythonDistribution {
             .into())
         })?;
 
-        let preferred_variants = match preferred_variants.get_type() {
-            "NoneType" => None,
-            "dict" => {
-                let mut m = HashMap::new();
-
-                for k in preferred_variants.into_iter()? {
-                    let v = preferred_variants.at(k.clone())?.to_string();
-                    m.insert(k.to_string(), v);
-                }
-
-                Some(m)
-            }
-            _ => panic!("type should have been validated above"),
-        };
-
         let context = env.get("CONTEXT").expect("CONTEXT not defined");
 
         let logger = context.downcast_apply(|x: &EnvironmentContext| x.logger.clone());
@@ -431,7 +589,12 @@ impl PythonDistribution {
             self.distribution
                 .as_ref()
                 .unwrap()
-                .filter_extension_modules(&logger, &filter, preferred_variants)
+                .filter_extension_modules(
+                    &logger,
+                    &filter,
+                    preferred_variants,
+                    &StdlibModuleFilter::default(),
+                )
                 .or_else(|e| {
                     Err(RuntimeError {
                         code: "PYOXIDIZER_BUILD",
@@ -539,8 +702,15 @@ impl PythonDistribution {
 
 starlark_module! { python_distribution_module =>
     #[allow(non_snake_case, clippy::ptr_arg)]
-    PythonDistribution(env env, sha256, local_path=None, url=None, flavor="standalone") {
-        PythonDistribution::from_args(&env, &sha256, &local_path, &url, &flavor)
+    PythonDistribution(
+        env env,
+        sha256,
+        local_path=None,
+        url=None,
+        flavor="standalone",
+        mirror_urls=None
+    ) {
+        PythonDistribution::from_args(&env, &sha256, &local_path, &url, &flavor, &mirror_urls)
     }
 
     #[allow(clippy::ptr_arg)]
@@ -576,7 +746,9 @@ starlark_module! { python_distribution_module =>
         preferred_extension_module_variants=None,
         include_sources=true,
         include_resources=false,
-        include_test=false
+        include_test=false,
+        stdlib_includes=None,
+        stdlib_excludes=None
     ) {
         this.downcast_apply_mut(|dist: &mut PythonDistribution| {
             dist.to_python_executable_starlark(
@@ -590,13 +762,25 @@ starlark_module! { python_distribution_module =>
                 &include_sources,
                 &include_resources,
                 &include_test,
+                &stdlib_includes,
+                &stdlib_excludes,
             )
         })
     }
 
     #[allow(clippy::ptr_arg)]
-    default_python_distribution(env env, flavor="standalone", build_target=None) {
-        PythonDistribution::default_python_distribution(&env, &flavor, &build_target)
+    default_python_distribution(
+        env env,
+        flavor="standalone",
+        build_target=None,
+        python_version=None)
+    {
+        PythonDistribution::default_python_distribution(
+            &env,
+            &flavor,
+            &build_target,
+            &python_version,
+        )
     }
 }
 
@@ -616,6 +800,7 @@ mod tests {
             .find_distribution(
                 crate::project_building::HOST,
                 &DistributionFlavor::Standalone,
+                None,
             )
             .unwrap();
 
@@ -624,6 +809,21 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_default_python_distribution_python_version() {
+        let dist = starlark_ok("default_python_distribution(python_version='3.8')");
+        assert_eq!(dist.get_type(), "PythonDistribution");
+
+        let err = starlark_nok("default_python_distribution(python_version='2.7')");
+        assert_eq!(
+            err.message,
+            format!(
+                "could not find default Python 2.7 distribution for {}",
+                crate::project_building::HOST
+            )
+        );
+    }
+
     #[test]
     fn test_default_python_distribution_bad_arg() {
         let err = starlark_nok("default_python_distribution(False)");
@@ -643,6 +843,7 @@ mod tests {
             .find_distribution(
                 crate::project_building::HOST,
                 &DistributionFlavor::StandaloneDynamic,
+                None,
             )
             .unwrap();
 
@@ -667,10 +868,11 @@ mod tests {
 
     #[test]
     fn test_python_distribution_url() {
-        let dist = starlark_ok("PythonDistribution('sha256', url='some_url')");
+        let dist = starlark_ok("PythonDistribution('sha256', url='https://a/dist.tar.zst')");
         let wanted = PythonDistributionLocation::Url {
-            url: "some_url".to_string(),
+            url: "https://a/dist.tar.zst".to_string(),
             sha256: "sha256".to_string(),
+            mirror_urls: vec![],
         };
 
         dist.downcast_apply(|x: &PythonDistribution| {
@@ -679,6 +881,108 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_python_distribution_env_url() {
+        std::env::set_var(
+            "PYOXIDIZER_TEST_DIST_URL",
+            "https://example.com/dist.tar.zst",
+        );
+
+        let dist = starlark_ok("PythonDistribution('sha256', url='env:PYOXIDIZER_TEST_DIST_URL')");
+        let wanted = PythonDistributionLocation::Url {
+            url: "https://example.com/dist.tar.zst".to_string(),
+            sha256: "sha256".to_string(),
+            mirror_urls: vec![],
+        };
+
+        dist.downcast_apply(|x: &PythonDistribution| {
+            assert_eq!(x.source, wanted);
+        });
+
+        let err = starlark_nok(
+            "PythonDistribution('sha256', url='env:PYOXIDIZER_TEST_MISSING_DIST_URL')",
+        );
+        assert_eq!(
+            err.message,
+            "environment variable PYOXIDIZER_TEST_MISSING_DIST_URL referenced by url is not set"
+        );
+
+        std::env::set_var(
+            "PYOXIDIZER_TEST_DIST_FTP_URL",
+            "ftp://example.com/dist.tar.zst",
+        );
+        let err =
+            starlark_nok("PythonDistribution('sha256', url='env:PYOXIDIZER_TEST_DIST_FTP_URL')");
+        assert_eq!(
+            err.message,
+            "url must be an http or https URL; got ftp://example.com/dist.tar.zst"
+        );
+    }
+
+    #[test]
+    fn test_python_distribution_invalid_url() {
+        let err = starlark_nok("PythonDistribution('sha256', url='some_url')");
+        assert_eq!(
+            err.message,
+            "url must be an http or https URL; got some_url"
+        );
+    }
+
+    #[test]
+    fn test_python_distribution_env_mirror_urls() {
+        std::env::set_var("PYOXIDIZER_TEST_DIST_MIRROR_URL", "https://b/dist.tar.zst");
+
+        let dist = starlark_ok(
+            "PythonDistribution('sha256', url='https://a/dist.tar.zst', mirror_urls=['env:PYOXIDIZER_TEST_DIST_MIRROR_URL'])",
+        );
+        let wanted = PythonDistributionLocation::Url {
+            url: "https://a/dist.tar.zst".to_string(),
+            sha256: "sha256".to_string(),
+            mirror_urls: vec!["https://b/dist.tar.zst".to_string()],
+        };
+
+        dist.downcast_apply(|x: &PythonDistribution| {
+            assert_eq!(x.source, wanted);
+        });
+
+        let err = starlark_nok(
+            "PythonDistribution('sha256', url='https://a/dist.tar.zst', mirror_urls=['env:PYOXIDIZER_TEST_MISSING_DIST_URL'])",
+        );
+        assert_eq!(
+            err.message,
+            "environment variable PYOXIDIZER_TEST_MISSING_DIST_URL referenced by mirror_urls is not set"
+        );
+    }
+
+    #[test]
+    fn test_python_distribution_mirror_urls() {
+        let dist = starlark_ok(
+            "PythonDistribution('sha256', url='https://a/dist.tar.zst', mirror_urls=['https://b/dist.tar.zst'])",
+        );
+        let wanted = PythonDistributionLocation::Url {
+            url: "https://a/dist.tar.zst".to_string(),
+            sha256: "sha256".to_string(),
+            mirror_urls: vec!["https://b/dist.tar.zst".to_string()],
+        };
+
+        dist.downcast_apply(|x: &PythonDistribution| {
+            assert_eq!(x.source, wanted);
+        });
+
+        let err = starlark_nok(
+            "PythonDistribution('sha256', url='https://a/dist.tar.zst', mirror_urls=['ftp://b/dist.tar.zst'])",
+        );
+        assert_eq!(
+            err.message,
+            "mirror_urls must be http or https URLs; got ftp://b/dist.tar.zst"
+        );
+
+        let err = starlark_nok(
+            "PythonDistribution('sha256', local_path='some_path', mirror_urls=['https://b/dist.tar.zst'])",
+        );
+        assert_eq!(err.message, "mirror_urls cannot be used with local_path");
+    }
+
     #[test]
     fn test_python_distribution_local_path() {
         let dist = starlark_ok("PythonDistribution('sha256', local_path='some_path')");
@@ -710,4 +1014,31 @@ mod tests {
 
         assert!(default_length < data_length);
     }
+
+    #[test]
+    fn test_extension_modules_preferred_variants() {
+        let mods = starlark_ok(
+            "default_python_distribution().extension_modules(preferred_variants={'_ssl': 'default'})",
+        );
+        assert_eq!(mods.get_type(), "list");
+
+        let mods = starlark_ok(
+            "default_python_distribution().extension_modules(preferred_variants={'_ssl': ['missing', 'default']})",
+        );
+        assert_eq!(mods.get_type(), "list");
+
+        let err = starlark_nok(
+            "default_python_distribution().extension_modules(preferred_variants={'_ssl': 42})",
+        );
+        assert!(err
+            .message
+            .starts_with("dict preferred_variants expects values"));
+
+        let err = starlark_nok(
+            "default_python_distribution().extension_modules(preferred_variants={'_ssl': [42]})",
+        );
+        assert!(err
+            .message
+            .starts_with("dict preferred_variants expects lists"));
+    }
 }
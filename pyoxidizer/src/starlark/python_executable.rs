@@ -11,13 +11,16 @@ use {
     },
     super::target::{BuildContext, BuildTarget, ResolvedTarget, RunMode},
     super::util::{
-        optional_dict_arg, optional_list_arg, required_bool_arg, required_list_arg,
-        required_str_arg, required_type_arg,
+        optional_dict_arg, optional_list_arg, optional_str_arg, required_bool_arg,
+        required_list_arg, required_optimize_level_arg, required_str_arg, required_type_arg,
     },
     crate::project_building::build_python_executable,
     crate::py_packaging::binary::PythonBinaryBuilder,
     anyhow::{anyhow, Context, Result},
-    python_packaging::resource::{BytecodeOptimizationLevel, PythonModuleBytecodeFromSource},
+    python_packaging::resource::{
+        DataLocation, PythonModuleBytecodeFromSource, PythonModuleSource,
+    },
+    python_packaging::resource_collection::ResourceDataKind,
     slog::{info, warn},
     starlark::environment::Environment,
     starlark::values::{
@@ -31,6 +34,7 @@ use {
     std::any::Any,
     std::cmp::Ordering,
     std::collections::HashMap,
+    std::convert::TryFrom,
     std::io::Write,
     std::ops::Deref,
     std::path::{Path, PathBuf},
@@ -101,19 +105,108 @@ impl BuildTarget for PythonExecutable {
     }
 }
 
+/// Whether a string is a valid, fully qualified Python module name.
+fn is_valid_module_name(name: &str) -> bool {
+    name.split('.').all(|part| {
+        let mut chars = part.chars();
+
+        match chars.next() {
+            Some(c) if c.is_alphabetic() || c == '_' => {
+                chars.all(|c| c.is_alphanumeric() || c == '_')
+            }
+            _ => false,
+        }
+    })
+}
+
+/// Build the arguments to pass to `pip install`.
+///
+/// Options derived from pip_install() arguments are placed before the
+/// user-provided `args`. A relative `constraints_path` is resolved against
+/// `cwd`, the directory of the config file.
+fn pip_install_args(
+    args: Vec<String>,
+    index_url: Option<&str>,
+    extra_index_urls: &[String],
+    require_hashes: bool,
+    constraints_path: Option<&str>,
+    cwd: &Path,
+) -> Vec<String> {
+    let mut res = Vec::new();
+
+    if let Some(url) = index_url {
+        res.push("--index-url".to_string());
+        res.push(url.to_string());
+    }
+    for url in extra_index_urls {
+        res.push("--extra-index-url".to_string());
+        res.push(url.clone());
+    }
+    if require_hashes {
+        res.push("--require-hashes".to_string());
+    }
+    if let Some(path) = constraints_path {
+        res.push("--constraint".to_string());
+        res.push(cwd.join(path).display().to_string());
+    }
+
+    res.extend(args);
+
+    res
+}
+
 // Starlark functions.
 impl PythonExecutable {
-    /// PythonExecutable.pip_install(args, extra_envs=None)
+    /// PythonExecutable.pip_install(args, extra_envs=None, index_url=None, extra_index_urls=None, require_hashes=false, constraints_path=None)
+    #[allow(clippy::too_many_arguments)]
     pub fn starlark_pip_install(
         &self,
         env: &Environment,
         args: &Value,
         extra_envs: &Value,
+        index_url: &Value,
+        extra_index_urls: &Value,
+        require_hashes: &Value,
+        constraints_path: &Value,
     ) -> ValueResult {
         required_list_arg("args", "string", &args)?;
         optional_dict_arg("extra_envs", "string", "string", &extra_envs)?;
+        let index_url = optional_str_arg("index_url", &index_url)?;
+        optional_list_arg("extra_index_urls", "string", &extra_index_urls)?;
+        let require_hashes = required_bool_arg("require_hashes", &require_hashes)?;
+        let constraints_path = optional_str_arg("constraints_path", &constraints_path)?;
+
+        let extra_index_urls: Vec<String> = match extra_index_urls.get_type() {
+            "list" => extra_index_urls
+                .into_iter()?
+                .map(|x| x.to_string())
+                .collect(),
+            _ => Vec::new(),
+        };
+
+        for url in index_url.iter().chain(extra_index_urls.iter()) {
+            if url::Url::parse(url).is_err() {
+                return Err(RuntimeError {
+                    code: INCORRECT_PARAMETER_TYPE_ERROR_CODE,
+                    message: format!("invalid package index URL: {}", url),
+                    label: "pip_install()".to_string(),
+                }
+                .into());
+            }
+        }
 
-        let args: Vec<String> = args.into_iter()?.map(|x| x.to_string()).collect();
+        let context = env.get("CONTEXT").expect("CONTEXT not defined");
+        let (logger, verbose, cwd) = context
+            .downcast_apply(|x: &EnvironmentContext| (x.logger.clone(), x.verbose, x.cwd.clone()));
+
+        let args = pip_install_args(
+            args.into_iter()?.map(|x| x.to_string()).collect(),
+            index_url.as_deref(),
+            &extra_index_urls,
+            require_hashes,
+            constraints_path.as_deref(),
+            &cwd,
+        );
 
         let extra_envs = match extra_envs.get_type() {
             "dict" => extra_envs
@@ -128,10 +221,6 @@ impl PythonExecutable {
             _ => panic!("should have validated type above"),
         };
 
-        let context = env.get("CONTEXT").expect("CONTEXT not defined");
-        let (logger, verbose) =
-            context.downcast_apply(|x: &EnvironmentContext| (x.logger.clone(), x.verbose));
-
         let resources = self
             .exe
             .pip_install(&logger, verbose, &args, &extra_envs)
@@ -217,6 +306,36 @@ impl PythonExecutable {
         ))
     }
 
+    /// PythonExecutable.make_python_module_source(name, source, is_package=false)
+    pub fn starlark_make_python_module_source(
+        &self,
+        name: &Value,
+        source: &Value,
+        is_package: &Value,
+    ) -> ValueResult {
+        let name = required_str_arg("name", &name)?;
+        let source = required_str_arg("source", &source)?;
+        let is_package = required_bool_arg("is_package", &is_package)?;
+
+        if !is_valid_module_name(&name) {
+            return Err(RuntimeError {
+                code: INCORRECT_PARAMETER_TYPE_ERROR_CODE,
+                message: format!("invalid Python module name: {}", name),
+                label: "make_python_module_source()".to_string(),
+            }
+            .into());
+        }
+
+        Ok(Value::new(PythonSourceModule {
+            module: PythonModuleSource {
+                name,
+                source: DataLocation::Memory(source.into_bytes()),
+                is_package,
+                cache_tag: self.exe.cache_tag().to_string(),
+            },
+        }))
+    }
+
     /// PythonExecutable.setup_py_install(package_path, extra_envs=None, extra_global_arguments=None)
     pub fn starlark_setup_py_install(
         &self,
@@ -385,27 +504,14 @@ impl PythonExecutable {
         optimize_level: &Value,
     ) -> ValueResult {
         required_type_arg("module", "PythonSourceModule", &module)?;
-        required_type_arg("optimize_level", "int", &optimize_level)?;
+        let optimize_level = required_optimize_level_arg(
+            "PythonExecutable.add_in_memory_module_bytecode()",
+            &optimize_level,
+        )?;
 
         let context = env.get("CONTEXT").expect("CONTEXT not set");
         let logger = context.downcast_apply(|x: &EnvironmentContext| x.logger.clone());
 
-        let optimize_level = optimize_level.to_int().unwrap();
-
-        let optimize_level = match optimize_level {
-            0 => BytecodeOptimizationLevel::Zero,
-            1 => BytecodeOptimizationLevel::One,
-            2 => BytecodeOptimizationLevel::Two,
-            i => {
-                return Err(RuntimeError {
-                    code: INCORRECT_PARAMETER_TYPE_ERROR_CODE,
-                    message: format!("optimize_level must be 0, 1, or 2: got {}", i),
-                    label: "invalid optimize_level value".to_string(),
-                }
-                .into());
-            }
-        };
-
         let m = module.downcast_apply(|m: &PythonSourceModule| m.module.clone());
         info!(&logger, "adding in-memory bytecode module {}", m.name);
         self.exe
@@ -440,27 +546,14 @@ impl PythonExecutable {
     ) -> ValueResult {
         let prefix = required_str_arg("prefix", &prefix)?;
         required_type_arg("module", "PythonSourceModule", &module)?;
-        required_type_arg("optimize_level", "int", &optimize_level)?;
+        let optimize_level = required_optimize_level_arg(
+            "PythonExecutable.add_filesystem_relative_module_bytecode()",
+            &optimize_level,
+        )?;
 
         let context = env.get("CONTEXT").expect("CONTEXT not set");
         let logger = context.downcast_apply(|x: &EnvironmentContext| x.logger.clone());
 
-        let optimize_level = optimize_level.to_int().unwrap();
-
-        let optimize_level = match optimize_level {
-            0 => BytecodeOptimizationLevel::Zero,
-            1 => BytecodeOptimizationLevel::One,
-            2 => BytecodeOptimizationLevel::Two,
-            i => {
-                return Err(RuntimeError {
-                    code: INCORRECT_PARAMETER_TYPE_ERROR_CODE,
-                    message: format!("optimize_level must be 0, 1, or 2: got {}", i),
-                    label: "invalid optimize_level value".to_string(),
-                }
-                .into());
-            }
-        };
-
         let m = module.downcast_apply(|m: &PythonSourceModule| m.module.clone());
         info!(
             &logger,
@@ -497,27 +590,12 @@ impl PythonExecutable {
         optimize_level: &Value,
     ) -> ValueResult {
         required_type_arg("module", "PythonSourceModule", &module)?;
-        required_type_arg("optimize_level", "int", &optimize_level)?;
+        let optimize_level =
+            required_optimize_level_arg("PythonExecutable.add_module_bytecode()", &optimize_level)?;
 
         let context = env.get("CONTEXT").expect("CONTEXT not set");
         let logger = context.downcast_apply(|x: &EnvironmentContext| x.logger.clone());
 
-        let optimize_level = optimize_level.to_int().unwrap();
-
-        let optimize_level = match optimize_level {
-            0 => BytecodeOptimizationLevel::Zero,
-            1 => BytecodeOptimizationLevel::One,
-            2 => BytecodeOptimizationLevel::Two,
-            i => {
-                return Err(RuntimeError {
-                    code: INCORRECT_PARAMETER_TYPE_ERROR_CODE,
-                    message: format!("optimize_level must be 0, 1, or 2: got {}", i),
-                    label: "invalid optimize_level value".to_string(),
-                }
-                .into());
-            }
-        };
-
         let m = module.downcast_apply(|m: &PythonSourceModule| m.module.clone());
         info!(&logger, "adding bytecode module {}", m.name);
         self.exe
@@ -1076,15 +1154,19 @@ impl PythonExecutable {
         }))
     }
 
-    /// PythonExecutable.filter_resources_from_files(files=None, glob_files=None)
+    /// PythonExecutable.filter_resources_from_files(files=None, glob_files=None, kinds=None, ignore_missing=false)
     pub fn starlark_filter_resources_from_files(
         &mut self,
         env: &Environment,
         files: &Value,
         glob_files: &Value,
+        kinds: &Value,
+        ignore_missing: &Value,
     ) -> ValueResult {
         optional_list_arg("files", "string", &files)?;
         optional_list_arg("glob_files", "string", &glob_files)?;
+        optional_list_arg("kinds", "string", &kinds)?;
+        let ignore_missing = required_bool_arg("ignore_missing", &ignore_missing)?;
 
         let files = match files.get_type() {
             "list" => files
@@ -1101,6 +1183,26 @@ impl PythonExecutable {
             _ => panic!("type should have been validated above"),
         };
 
+        let kinds = match kinds.get_type() {
+            "list" => Some(
+                kinds
+                    .into_iter()?
+                    .map(|x| {
+                        ResourceDataKind::try_from(x.to_str().as_str()).or_else(|e| {
+                            Err(RuntimeError {
+                                code: INCORRECT_PARAMETER_TYPE_ERROR_CODE,
+                                message: e.to_string(),
+                                label: "filter_resources_from_files()".to_string(),
+                            }
+                            .into())
+                        })
+                    })
+                    .collect::<Result<Vec<_>, ValueError>>()?,
+            ),
+            "NoneType" => None,
+            _ => panic!("type should have been validated above"),
+        };
+
         let files_refs = files.iter().map(|x| x.as_ref()).collect::<Vec<&Path>>();
         let glob_files_refs = glob_files.iter().map(|x| x.as_ref()).collect::<Vec<&str>>();
 
@@ -1108,7 +1210,13 @@ impl PythonExecutable {
         let logger = context.downcast_apply(|x: &EnvironmentContext| x.logger.clone());
 
         self.exe
-            .filter_resources_from_files(&logger, &files_refs, &glob_files_refs)
+            .filter_resources_from_files(
+                &logger,
+                &files_refs,
+                &glob_files_refs,
+                kinds.as_ref().map(|x| x.as_slice()),
+                ignore_missing,
+            )
             .or_else(|e| {
                 Err(RuntimeError {
                     code: "RUNTIME_ERROR",
@@ -1124,9 +1232,26 @@ impl PythonExecutable {
 
 starlark_module! { python_executable_env =>
     #[allow(non_snake_case, clippy::ptr_arg)]
-    PythonExecutable.pip_install(env env, this, args, extra_envs=None) {
+    PythonExecutable.pip_install(
+        env env,
+        this,
+        args,
+        extra_envs=None,
+        index_url=None,
+        extra_index_urls=None,
+        require_hashes=false,
+        constraints_path=None)
+    {
         this.downcast_apply(|exe: &PythonExecutable| {
-            exe.starlark_pip_install(&env, &args, &extra_envs)
+            exe.starlark_pip_install(
+                &env,
+                &args,
+                &extra_envs,
+                &index_url,
+                &extra_index_urls,
+                &require_hashes,
+                &constraints_path,
+            )
         })
     }
 
@@ -1153,6 +1278,18 @@ starlark_module! { python_executable_env =>
         })
     }
 
+    #[allow(non_snake_case, clippy::ptr_arg)]
+    PythonExecutable.make_python_module_source(
+        this,
+        name,
+        source,
+        is_package=false
+    ) {
+        this.downcast_apply(|exe: &PythonExecutable| {
+            exe.starlark_make_python_module_source(&name, &source, &is_package)
+        })
+    }
+
     #[allow(non_snake_case, clippy::ptr_arg)]
     PythonExecutable.setup_py_install(
         env env,
@@ -1404,10 +1541,18 @@ starlark_module! { python_executable_env =>
         env env,
         this,
         files=None,
-        glob_files=None)
+        glob_files=None,
+        kinds=None,
+        ignore_missing=false)
     {
         this.downcast_apply_mut(|exe: &mut PythonExecutable| {
-            exe.starlark_filter_resources_from_files(&env, &files, &glob_files)
+            exe.starlark_filter_resources_from_files(
+                &env,
+                &files,
+                &glob_files,
+                &kinds,
+                &ignore_missing,
+            )
         })
     }
 
@@ -1459,6 +1604,79 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_stdlib_excludes() {
+        let mut env = starlark_env();
+
+        starlark_eval_in_env(&mut env, "dist = default_python_distribution()").unwrap();
+
+        let exe = starlark_eval_in_env(
+            &mut env,
+            "dist.to_python_executable('testapp', stdlib_excludes=['json', 'turtle*'])",
+        )
+        .unwrap();
+
+        exe.downcast_apply(|exe: &PythonExecutable| {
+            let sources = exe.exe.in_memory_module_sources();
+            assert!(!sources.contains_key("json"));
+            assert!(!sources.contains_key("json.decoder"));
+            assert!(!sources.contains_key("turtle"));
+            assert!(sources.contains_key("os"));
+        });
+
+        let exe = starlark_eval_in_env(
+            &mut env,
+            "dist.to_python_executable('testapp', stdlib_includes=['json'])",
+        )
+        .unwrap();
+
+        exe.downcast_apply(|exe: &PythonExecutable| {
+            let sources = exe.exe.in_memory_module_sources();
+            assert!(sources.contains_key("json"));
+            assert!(sources.contains_key("json.decoder"));
+            assert!(!sources.contains_key("os"));
+        });
+    }
+
+    #[test]
+    fn test_filter_resources_from_files_invalid_kind() {
+        let mut env = starlark_env();
+
+        starlark_eval_in_env(&mut env, "dist = default_python_distribution()").unwrap();
+        starlark_eval_in_env(&mut env, "exe = dist.to_python_executable('testapp')").unwrap();
+
+        let err = starlark_eval_in_env(&mut env, "exe.filter_resources_from_files(kinds=['bad'])")
+            .unwrap_err();
+        assert!(err.message.starts_with("invalid resource kind: bad"));
+    }
+
+    #[test]
+    fn test_filter_resources_from_files_ignore_missing() {
+        let mut env = starlark_env();
+
+        starlark_eval_in_env(&mut env, "dist = default_python_distribution()").unwrap();
+        starlark_eval_in_env(&mut env, "exe = dist.to_python_executable('testapp')").unwrap();
+
+        let err = starlark_eval_in_env(
+            &mut env,
+            "exe.filter_resources_from_files(files=['/does/not/exist'])",
+        )
+        .unwrap_err();
+        assert!(err.message.contains("/does/not/exist"));
+
+        starlark_eval_in_env(
+            &mut env,
+            "exe.filter_resources_from_files(files=['/does/not/exist'], ignore_missing=True)",
+        )
+        .unwrap();
+
+        let exe = starlark_eval_in_env(&mut env, "exe").unwrap();
+        exe.downcast_apply(|exe: &PythonExecutable| {
+            let sources = exe.exe.in_memory_module_sources();
+            assert!(sources.contains_key("os"));
+        });
+    }
+
     #[test]
     fn test_pip_install_simple() {
         let mut env = starlark_env();
@@ -1485,6 +1703,153 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_make_python_module_source() {
+        let mut env = starlark_env();
+
+        starlark_eval_in_env(&mut env, "dist = default_python_distribution()").unwrap();
+        starlark_eval_in_env(&mut env, "exe = dist.to_python_executable('testapp')").unwrap();
+
+        starlark_eval_in_env(
+            &mut env,
+            "m = exe.make_python_module_source('foo.bar', 'import os', is_package=True)",
+        )
+        .unwrap();
+
+        let m = starlark_eval_in_env(&mut env, "m").unwrap();
+        assert_eq!(m.get_type(), "PythonSourceModule");
+        m.downcast_apply(|x: &PythonSourceModule| {
+            assert_eq!(x.module.name, "foo.bar");
+            assert_eq!(x.module.source.resolve().unwrap(), b"import os".to_vec());
+            assert!(x.module.is_package);
+        });
+
+        starlark_eval_in_env(&mut env, "exe.add_in_memory_python_resource(m)").unwrap();
+        let exe = starlark_eval_in_env(&mut env, "exe").unwrap();
+        exe.downcast_apply(|exe: &PythonExecutable| {
+            assert!(exe.exe.in_memory_module_sources().contains_key("foo.bar"));
+        });
+
+        let err = starlark_eval_in_env(&mut env, "exe.make_python_module_source('foo..bar', '')")
+            .unwrap_err();
+        assert_eq!(err.message, "invalid Python module name: foo..bar");
+    }
+
+    #[test]
+    fn test_add_module_bytecode_invalid_optimize_level() {
+        let mut env = starlark_env();
+
+        starlark_eval_in_env(&mut env, "dist = default_python_distribution()").unwrap();
+        starlark_eval_in_env(&mut env, "exe = dist.to_python_executable('testapp')").unwrap();
+        starlark_eval_in_env(
+            &mut env,
+            "m = exe.make_python_module_source('foo', 'import os')",
+        )
+        .unwrap();
+
+        let err = starlark_eval_in_env(&mut env, "exe.add_module_bytecode(m, optimize_level=3)")
+            .unwrap_err();
+        assert_eq!(
+            err.message,
+            "PythonExecutable.add_module_bytecode(): optimize_level must be 0, 1, or 2: got 3"
+        );
+
+        starlark_eval_in_env(&mut env, "exe.add_module_bytecode(m, optimize_level=2)").unwrap();
+    }
+
+    #[test]
+    fn test_pip_install_invalid_index_url() {
+        let mut env = starlark_env();
+
+        starlark_eval_in_env(&mut env, "dist = default_python_distribution()").unwrap();
+        starlark_eval_in_env(&mut env, "exe = dist.to_python_executable('testapp')").unwrap();
+
+        let err = starlark_eval_in_env(
+            &mut env,
+            "exe.pip_install(['pyflakes'], index_url='not a url')",
+        )
+        .unwrap_err();
+        assert_eq!(err.message, "invalid package index URL: not a url");
+
+        let err = starlark_eval_in_env(
+            &mut env,
+            "exe.pip_install(['pyflakes'], extra_index_urls=['https://a/simple', 'bad'])",
+        )
+        .unwrap_err();
+        assert_eq!(err.message, "invalid package index URL: bad");
+    }
+
+    #[test]
+    fn test_pip_install_args_index_urls() {
+        let args = pip_install_args(
+            vec!["pyflakes".to_string()],
+            Some("https://example.com/simple"),
+            &[
+                "https://a.example.com/simple".to_string(),
+                "https://b.example.com/simple".to_string(),
+            ],
+            false,
+            None,
+            Path::new("/project"),
+        );
+
+        assert_eq!(
+            args,
+            vec![
+                "--index-url",
+                "https://example.com/simple",
+                "--extra-index-url",
+                "https://a.example.com/simple",
+                "--extra-index-url",
+                "https://b.example.com/simple",
+                "pyflakes",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_pip_install_args_hashes_constraints() {
+        let cwd = PathBuf::from("/project");
+
+        let args = pip_install_args(
+            vec!["-r".to_string(), "requirements.txt".to_string()],
+            Some("https://example.com/simple"),
+            &[],
+            true,
+            Some("constraints.txt"),
+            &cwd,
+        );
+
+        assert_eq!(
+            args,
+            vec![
+                "--index-url".to_string(),
+                "https://example.com/simple".to_string(),
+                "--require-hashes".to_string(),
+                "--constraint".to_string(),
+                cwd.join("constraints.txt").display().to_string(),
+                "-r".to_string(),
+                "requirements.txt".to_string(),
+            ]
+        );
+
+        // Absolute constraints paths are used as-is.
+        let absolute = std::env::temp_dir().join("constraints.txt");
+        let args = pip_install_args(
+            vec![],
+            None,
+            &[],
+            false,
+            Some(&absolute.display().to_string()),
+            &cwd,
+        );
+
+        assert_eq!(
+            args,
+            vec!["--constraint".to_string(), absolute.display().to_string()]
+        );
+    }
+
     #[test]
     fn test_read_package_root_simple() -> Result<()> {
         let temp_dir = tempdir::TempDir::new("pyoxidizer-test")?;
@@ -4,9 +4,13 @@
 
 use crate::py_packaging::config::RunMode;
 use {
-    super::util::{optional_list_arg, optional_str_arg, required_bool_arg, required_type_arg},
+    super::util::{
+        optional_bool_arg, optional_list_arg, optional_str_arg, optional_type_arg,
+        required_bool_arg, required_optimize_level_arg, required_type_arg,
+    },
     crate::py_packaging::config::{
-        default_raw_allocator, EmbeddedPythonConfig, RawAllocator, TerminfoResolution,
+        default_raw_allocator, CoerceCLocale, EmbeddedPythonConfig, RawAllocator,
+        TerminfoResolution,
     },
     starlark::environment::Environment,
     starlark::values::{
@@ -59,8 +63,11 @@ impl EmbeddedPythonConfig {
     pub fn starlark_new(
         env: &Environment,
         bytes_warning: &Value,
+        coerce_c_locale: &Value,
+        development_mode: &Value,
         ignore_environment: &Value,
         inspect: &Value,
+        int_max_str_digits: &Value,
         interactive: &Value,
         isolated: &Value,
         legacy_windows_fs_encoding: &Value,
@@ -76,6 +83,7 @@ impl EmbeddedPythonConfig {
         run_module: &Value,
         run_noop: &Value,
         run_repl: &Value,
+        run_setup_eval: &Value,
         site_import: &Value,
         sys_frozen: &Value,
         sys_meipass: &Value,
@@ -86,29 +94,38 @@ impl EmbeddedPythonConfig {
         use_hash_seed: &Value,
         user_site_directory: &Value,
         verbose: &Value,
+        warn_default_encoding: &Value,
         write_bytecode: &Value,
         write_modules_directory_env: &Value,
+        write_modules_directory: &Value,
     ) -> ValueResult {
         required_type_arg("bytes_warning", "int", &bytes_warning)?;
+        let coerce_c_locale = optional_str_arg("coerce_c_locale", &coerce_c_locale)?;
+        let development_mode = required_bool_arg("development_mode", &development_mode)?;
         let ignore_environment = required_bool_arg("ignore_environment", &ignore_environment)?;
         let inspect = required_bool_arg("inspect", &inspect)?;
+        optional_type_arg("int_max_str_digits", "int", &int_max_str_digits)?;
         let interactive = required_bool_arg("interactive", &interactive)?;
         let isolated = required_bool_arg("isolated", &isolated)?;
         let legacy_windows_fs_encoding =
             required_bool_arg("legacy_windows_fs_encoding", &legacy_windows_fs_encoding)?;
         let legacy_windows_stdio =
             required_bool_arg("legacy_windows_stdio", &legacy_windows_stdio)?;
-        required_type_arg("optimize_level", "int", &optimize_level)?;
+        let optimize_level = i64::from(i32::from(required_optimize_level_arg(
+            "PythonInterpreterConfig",
+            &optimize_level,
+        )?));
         let parser_debug = required_bool_arg("parser_debug", &parser_debug)?;
         let stdio_encoding = optional_str_arg("stdio_encoding", &stdio_encoding)?;
         let unbuffered_stdio = required_bool_arg("unbuffered_stdio", &unbuffered_stdio)?;
-        let filesystem_importer = required_bool_arg("filesystem_importer", &filesystem_importer)?;
+        let filesystem_importer = optional_bool_arg("filesystem_importer", &filesystem_importer)?;
         let quiet = required_bool_arg("quiet", &quiet)?;
         let run_eval = optional_str_arg("run_eval", &run_eval)?;
         let run_file = optional_str_arg("run_file", &run_file)?;
         let run_module = optional_str_arg("run_module", &run_module)?;
         let run_noop = required_bool_arg("run_noop", &run_noop)?;
         let run_repl = required_bool_arg("run_repl", &run_repl)?;
+        optional_list_arg("run_setup_eval", "string", &run_setup_eval)?;
         let sys_frozen = required_bool_arg("sys_frozen", &sys_frozen)?;
         let sys_meipass = required_bool_arg("sys_meipass", &sys_meipass)?;
         optional_list_arg("sys_paths", "string", &sys_paths)?;
@@ -119,9 +136,13 @@ impl EmbeddedPythonConfig {
         let use_hash_seed = required_bool_arg("use_hash_seed", &use_hash_seed)?;
         let user_site_directory = required_bool_arg("user_site_directory", &user_site_directory)?;
         required_type_arg("verbose", "int", &verbose)?;
+        let warn_default_encoding =
+            required_bool_arg("warn_default_encoding", &warn_default_encoding)?;
         let write_bytecode = required_bool_arg("write_bytecode", &write_bytecode)?;
         let write_modules_directory_env =
             optional_str_arg("write_modules_directory_env", &write_modules_directory_env)?;
+        let write_modules_directory =
+            optional_str_arg("write_modules_directory", &write_modules_directory)?;
 
         let build_target = env.get("BUILD_TARGET_TRIPLE").unwrap().to_str();
 
@@ -163,16 +184,88 @@ impl EmbeddedPythonConfig {
             RunMode::Repl
         };
 
+        let run_mode = match run_setup_eval.get_type() {
+            "list" => {
+                let mut steps = run_setup_eval
+                    .into_iter()
+                    .unwrap()
+                    .map(|x| RunMode::Eval { code: x.to_str() })
+                    .collect::<Vec<_>>();
+
+                if steps.is_empty() {
+                    return Err(RuntimeError {
+                        code: INCORRECT_PARAMETER_TYPE_ERROR_CODE,
+                        message: "run_setup_eval must not be empty".to_string(),
+                        label: "PythonInterpreterConfig()".to_string(),
+                    }
+                    .into());
+                }
+
+                steps.push(run_mode);
+
+                RunMode::Sequence { steps }
+            }
+            _ => run_mode,
+        };
+
+        let int_max_str_digits = match int_max_str_digits.get_type() {
+            "int" => {
+                let value = int_max_str_digits.to_int().unwrap();
+
+                // CPython rejects limits below this threshold, with 0 meaning unlimited.
+                if value != 0 && value < 640 {
+                    return Err(RuntimeError {
+                        code: INCORRECT_PARAMETER_TYPE_ERROR_CODE,
+                        message: "int_max_str_digits must be 0 or >= 640".to_string(),
+                        label: "PythonInterpreterConfig()".to_string(),
+                    }
+                    .into());
+                }
+
+                Some(value)
+            }
+            _ => None,
+        };
+
         let (stdio_encoding_name, stdio_encoding_errors) = if let Some(ref v) = stdio_encoding {
-            let values: Vec<&str> = v.split(':').collect();
-            (Some(values[0].to_string()), Some(values[1].to_string()))
+            match v.splitn(2, ':').collect::<Vec<&str>>().as_slice() {
+                [name, errors] if !name.is_empty() => {
+                    (Some(name.to_string()), Some(errors.to_string()))
+                }
+                _ => {
+                    return Err(RuntimeError {
+                        code: INCORRECT_PARAMETER_TYPE_ERROR_CODE,
+                        message: "stdio_encoding must be in 'encoding:errors' format".to_string(),
+                        label: "PythonInterpreterConfig()".to_string(),
+                    }
+                    .into());
+                }
+            }
         } else {
             (None, None)
         };
 
+        let coerce_c_locale = match coerce_c_locale {
+            Some(x) => Some(match x.as_ref() {
+                "off" => CoerceCLocale::Off,
+                "on" => CoerceCLocale::On,
+                "warn" => CoerceCLocale::Warn,
+                _ => {
+                    return Err(RuntimeError {
+                        code: INCORRECT_PARAMETER_TYPE_ERROR_CODE,
+                        message: "coerce_c_locale must be 'off', 'on', or 'warn'".to_string(),
+                        label: "PythonInterpreterConfig()".to_string(),
+                    }
+                    .into());
+                }
+            }),
+            None => None,
+        };
+
         let raw_allocator = match raw_allocator {
             Some(x) => match x.as_ref() {
                 "jemalloc" => RawAllocator::Jemalloc,
+                "mimalloc" => RawAllocator::Mimalloc,
                 "rust" => RawAllocator::Rust,
                 "system" => RawAllocator::System,
                 _ => {
@@ -190,6 +283,7 @@ impl EmbeddedPythonConfig {
         let terminfo_resolution = match terminfo_resolution {
             Some(x) => match x.as_ref() {
                 "dynamic" => TerminfoResolution::Dynamic,
+                "system" => TerminfoResolution::SystemPaths,
                 "static" => TerminfoResolution::Static(if let Some(dirs) = terminfo_dirs {
                     dirs
                 } else {
@@ -204,8 +298,10 @@ impl EmbeddedPythonConfig {
                 _ => {
                     return Err(RuntimeError {
                         code: INCORRECT_PARAMETER_TYPE_ERROR_CODE,
-                        message: "terminfo_resolution must be 'dynamic' or 'static'".to_string(),
-                        label: "terminfo_resolution must be 'dynamic' or 'static'".to_string(),
+                        message: "terminfo_resolution must be 'dynamic', 'static', or 'system'"
+                            .to_string(),
+                        label: "terminfo_resolution must be 'dynamic', 'static', or 'system'"
+                            .to_string(),
                     }
                     .into());
                 }
@@ -222,17 +318,30 @@ impl EmbeddedPythonConfig {
             _ => Vec::new(),
         };
 
-        let filesystem_importer = filesystem_importer || !sys_paths.is_empty();
+        if sys_paths.iter().any(|p: &String| p.is_empty()) {
+            return Err(RuntimeError {
+                code: INCORRECT_PARAMETER_TYPE_ERROR_CODE,
+                message: "sys_paths entries must not be empty".to_string(),
+                label: "sys_paths".to_string(),
+            }
+            .into());
+        }
+
+        // sys_paths implies the filesystem importer unless it was explicitly disabled.
+        let filesystem_importer = filesystem_importer.unwrap_or_else(|| !sys_paths.is_empty());
 
         Ok(Value::new(EmbeddedPythonConfig {
             bytes_warning: bytes_warning.to_int().unwrap() as i32,
+            coerce_c_locale,
+            development_mode,
             ignore_environment,
             inspect,
+            int_max_str_digits,
             interactive,
             isolated,
             legacy_windows_fs_encoding,
             legacy_windows_stdio,
-            optimize_level: optimize_level.to_int().unwrap(),
+            optimize_level,
             parser_debug,
             quiet,
             stdio_encoding_name,
@@ -249,8 +358,10 @@ impl EmbeddedPythonConfig {
             use_hash_seed,
             user_site_directory,
             verbose: verbose.to_int().unwrap() as i32,
+            warn_default_encoding,
             write_bytecode,
             write_modules_directory_env,
+            write_modules_directory,
         }))
     }
 }
@@ -260,8 +371,11 @@ starlark_module! { embedded_python_config_module =>
     PythonInterpreterConfig(
         env env,
         bytes_warning=0,
+        coerce_c_locale=None,
+        development_mode=false,
         ignore_environment=true,
         inspect=false,
+        int_max_str_digits=None,
         interactive=false,
         isolated=true,
         legacy_windows_fs_encoding=false,
@@ -270,13 +384,14 @@ starlark_module! { embedded_python_config_module =>
         parser_debug=false,
         stdio_encoding=None,
         unbuffered_stdio=false,
-        filesystem_importer=false,
+        filesystem_importer=None,
         quiet=false,
         run_eval=None,
         run_file=None,
         run_module=None,
         run_noop=false,
         run_repl=false,
+        run_setup_eval=None,
         site_import=false,
         sys_frozen=false,
         sys_meipass=false,
@@ -287,14 +402,19 @@ starlark_module! { embedded_python_config_module =>
         use_hash_seed=false,
         user_site_directory=false,
         verbose=0,
+        warn_default_encoding=false,
         write_bytecode=false,
-        write_modules_directory_env=None
+        write_modules_directory_env=None,
+        write_modules_directory=None
     ) {
         EmbeddedPythonConfig::starlark_new(
             &env,
             &bytes_warning,
+            &coerce_c_locale,
+            &development_mode,
             &ignore_environment,
             &inspect,
+            &int_max_str_digits,
             &interactive,
             &isolated,
             &legacy_windows_fs_encoding,
@@ -310,6 +430,7 @@ starlark_module! { embedded_python_config_module =>
             &run_module,
             &run_noop,
             &run_repl,
+            &run_setup_eval,
             &site_import,
             &sys_frozen,
             &sys_meipass,
@@ -320,8 +441,10 @@ starlark_module! { embedded_python_config_module =>
             &use_hash_seed,
             &user_site_directory,
             &verbose,
+            &warn_default_encoding,
             &write_bytecode,
-            &write_modules_directory_env
+            &write_modules_directory_env,
+            &write_modules_directory
         )
     }
 }
@@ -337,8 +460,11 @@ mod tests {
 
         let wanted = crate::py_packaging::config::EmbeddedPythonConfig {
             bytes_warning: 0,
+            coerce_c_locale: None,
+            development_mode: false,
             ignore_environment: true,
             inspect: false,
+            int_max_str_digits: None,
             interactive: false,
             isolated: true,
             legacy_windows_fs_encoding: false,
@@ -348,6 +474,7 @@ mod tests {
             quiet: false,
             use_hash_seed: false,
             verbose: 0,
+            warn_default_encoding: false,
             stdio_encoding_name: None,
             stdio_encoding_errors: None,
             unbuffered_stdio: false,
@@ -362,6 +489,7 @@ mod tests {
             user_site_directory: false,
             write_bytecode: false,
             write_modules_directory_env: None,
+            write_modules_directory: None,
         };
 
         c.downcast_apply(|x: &EmbeddedPythonConfig| assert_eq!(x, &wanted));
@@ -373,10 +501,66 @@ mod tests {
         c.downcast_apply(|x: &EmbeddedPythonConfig| assert_eq!(x.bytes_warning, 2));
     }
 
+    #[test]
+    fn test_coerce_c_locale() {
+        for (value, wanted) in &[
+            ("off", CoerceCLocale::Off),
+            ("on", CoerceCLocale::On),
+            ("warn", CoerceCLocale::Warn),
+        ] {
+            let c = starlark_ok(&format!(
+                "PythonInterpreterConfig(coerce_c_locale='{}')",
+                value
+            ));
+            c.downcast_apply(|x: &EmbeddedPythonConfig| {
+                assert_eq!(x.coerce_c_locale, Some(wanted.clone()))
+            });
+        }
+
+        let err = starlark_nok("PythonInterpreterConfig(coerce_c_locale='bad')");
+        assert_eq!(
+            err.message,
+            "coerce_c_locale must be 'off', 'on', or 'warn'"
+        );
+    }
+
+    #[test]
+    fn test_development_mode() {
+        let c = starlark_ok("PythonInterpreterConfig(development_mode=True)");
+        c.downcast_apply(|x: &EmbeddedPythonConfig| assert!(x.development_mode));
+    }
+
+    #[test]
+    fn test_int_max_str_digits() {
+        let c = starlark_ok("PythonInterpreterConfig(int_max_str_digits=0)");
+        c.downcast_apply(|x: &EmbeddedPythonConfig| assert_eq!(x.int_max_str_digits, Some(0)));
+
+        let c = starlark_ok("PythonInterpreterConfig(int_max_str_digits=4300)");
+        c.downcast_apply(|x: &EmbeddedPythonConfig| assert_eq!(x.int_max_str_digits, Some(4300)));
+
+        let err = starlark_nok("PythonInterpreterConfig(int_max_str_digits=100)");
+        assert_eq!(err.message, "int_max_str_digits must be 0 or >= 640");
+    }
+
+    #[test]
+    fn test_warn_default_encoding() {
+        let c = starlark_ok("PythonInterpreterConfig(warn_default_encoding=True)");
+        c.downcast_apply(|x: &EmbeddedPythonConfig| assert!(x.warn_default_encoding));
+
+        let err = starlark_nok("PythonInterpreterConfig(warn_default_encoding=1)");
+        assert!(err.message.contains("warn_default_encoding"));
+    }
+
     #[test]
     fn test_optimize_level() {
         let c = starlark_ok("PythonInterpreterConfig(optimize_level=1)");
         c.downcast_apply(|x: &EmbeddedPythonConfig| assert_eq!(x.optimize_level, 1));
+
+        let err = starlark_nok("PythonInterpreterConfig(optimize_level=3)");
+        assert_eq!(
+            err.message,
+            "PythonInterpreterConfig: optimize_level must be 0, 1, or 2: got 3"
+        );
     }
 
     #[test]
@@ -387,6 +571,22 @@ mod tests {
             // Setting sys_paths enables filesystem importer.
             assert!(x.filesystem_importer);
         });
+
+        let c = starlark_ok("PythonInterpreterConfig(sys_paths=['$ORIGIN/lib', '$APPDIR/extra'])");
+        c.downcast_apply(|x: &EmbeddedPythonConfig| {
+            // Tokens are resolved at run-time, not by the config.
+            assert_eq!(x.sys_paths, ["$ORIGIN/lib", "$APPDIR/extra"]);
+        });
+
+        let c =
+            starlark_ok("PythonInterpreterConfig(sys_paths=['foo'], filesystem_importer=False)");
+        c.downcast_apply(|x: &EmbeddedPythonConfig| {
+            assert_eq!(x.sys_paths, ["foo"]);
+            assert!(!x.filesystem_importer);
+        });
+
+        let err = starlark_nok("PythonInterpreterConfig(sys_paths=['foo', ''])");
+        assert_eq!(err.message, "sys_paths entries must not be empty");
     }
 
     #[test]
@@ -395,7 +595,19 @@ mod tests {
         c.downcast_apply(|x: &EmbeddedPythonConfig| {
             assert_eq!(x.stdio_encoding_name, Some("foo".to_string()));
             assert_eq!(x.stdio_encoding_errors, Some("strict".to_string()));
-        })
+        });
+
+        let err = starlark_nok("PythonInterpreterConfig(stdio_encoding='utf-8')");
+        assert_eq!(
+            err.message,
+            "stdio_encoding must be in 'encoding:errors' format"
+        );
+
+        let err = starlark_nok("PythonInterpreterConfig(stdio_encoding=':strict')");
+        assert_eq!(
+            err.message,
+            "stdio_encoding must be in 'encoding:errors' format"
+        );
     }
 
     #[test]
@@ -408,6 +620,10 @@ mod tests {
         c.downcast_apply(|x: &EmbeddedPythonConfig| {
             assert_eq!(x.raw_allocator, RawAllocator::Jemalloc);
         });
+        let c = starlark_ok("PythonInterpreterConfig(raw_allocator='mimalloc')");
+        c.downcast_apply(|x: &EmbeddedPythonConfig| {
+            assert_eq!(x.raw_allocator, RawAllocator::Mimalloc);
+        });
         let c = starlark_ok("PythonInterpreterConfig(raw_allocator='rust')");
         c.downcast_apply(|x: &EmbeddedPythonConfig| {
             assert_eq!(x.raw_allocator, RawAllocator::Rust);
@@ -469,6 +685,31 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_run_setup_eval() {
+        let c = starlark_ok(
+            "PythonInterpreterConfig(run_setup_eval=['import foo'], run_module='main')",
+        );
+        c.downcast_apply(|x: &EmbeddedPythonConfig| {
+            assert_eq!(
+                x.run_mode,
+                RunMode::Sequence {
+                    steps: vec![
+                        RunMode::Eval {
+                            code: "import foo".to_string()
+                        },
+                        RunMode::Module {
+                            module: "main".to_string()
+                        },
+                    ]
+                }
+            );
+        });
+
+        let err = starlark_nok("PythonInterpreterConfig(run_setup_eval=[])");
+        assert_eq!(err.message, "run_setup_eval must not be empty");
+    }
+
     #[test]
     fn test_terminfo_resolution() {
         let c = starlark_ok("PythonInterpreterConfig(terminfo_resolution=None)");
@@ -485,5 +726,33 @@ mod tests {
                 TerminfoResolution::Static("foo".to_string())
             );
         });
+
+        let c = starlark_ok("PythonInterpreterConfig(terminfo_resolution='system')");
+        c.downcast_apply(|x: &EmbeddedPythonConfig| {
+            assert_eq!(x.terminfo_resolution, TerminfoResolution::SystemPaths);
+        });
+
+        let err = starlark_nok("PythonInterpreterConfig(terminfo_resolution='bad')");
+        assert_eq!(
+            err.message,
+            "terminfo_resolution must be 'dynamic', 'static', or 'system'"
+        );
+    }
+
+    #[test]
+    fn test_write_modules_directory() {
+        let c = starlark_ok(
+            "PythonInterpreterConfig(write_modules_directory_env='MODULES_DIR', write_modules_directory='$ORIGIN/modules')",
+        );
+        c.downcast_apply(|x: &EmbeddedPythonConfig| {
+            assert_eq!(
+                x.write_modules_directory_env,
+                Some("MODULES_DIR".to_string())
+            );
+            assert_eq!(
+                x.write_modules_directory,
+                Some("$ORIGIN/modules".to_string())
+            );
+        });
     }
 }
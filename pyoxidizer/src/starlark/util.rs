@@ -2,6 +2,7 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at https://mozilla.org/MPL/2.0/.
 
+use python_packaging::resource::BytecodeOptimizationLevel;
 use starlark::values::{RuntimeError, Value, ValueError, INCORRECT_PARAMETER_TYPE_ERROR_CODE};
 
 pub fn required_type_arg(arg_name: &str, arg_type: &str, value: &Value) -> Result<(), ValueError> {
@@ -71,6 +72,22 @@ pub fn required_bool_arg(name: &str, value: &Value) -> Result<bool, ValueError>
     }
 }
 
+pub fn optional_bool_arg(name: &str, value: &Value) -> Result<Option<bool>, ValueError> {
+    match value.get_type() {
+        "NoneType" => Ok(None),
+        "bool" => Ok(Some(value.to_bool())),
+        t => Err(RuntimeError {
+            code: INCORRECT_PARAMETER_TYPE_ERROR_CODE,
+            message: format!(
+                "function expects an optional bool for {}; got type {}",
+                name, t
+            ),
+            label: format!("expected type bool; got {}", t),
+        }
+        .into()),
+    }
+}
+
 pub fn required_list_arg(
     arg_name: &str,
     value_type: &str,
@@ -184,3 +201,33 @@ pub fn optional_dict_arg(
 
     required_dict_arg(arg_name, key_type, value_type, value)
 }
+
+/// Validate a Python bytecode optimization level.
+pub fn validate_optimize_level(v: i64) -> Result<BytecodeOptimizationLevel, String> {
+    match v {
+        0 => Ok(BytecodeOptimizationLevel::Zero),
+        1 => Ok(BytecodeOptimizationLevel::One),
+        2 => Ok(BytecodeOptimizationLevel::Two),
+        _ => Err(format!("optimize_level must be 0, 1, or 2: got {}", v)),
+    }
+}
+
+/// Obtain a validated `optimize_level` argument.
+///
+/// `caller` identifies the function or type receiving the argument and is
+/// included in error messages.
+pub fn required_optimize_level_arg(
+    caller: &str,
+    value: &Value,
+) -> Result<BytecodeOptimizationLevel, ValueError> {
+    required_type_arg("optimize_level", "int", value)?;
+
+    validate_optimize_level(value.to_int().unwrap()).or_else(|e| {
+        Err(RuntimeError {
+            code: INCORRECT_PARAMETER_TYPE_ERROR_CODE,
+            message: format!("{}: {}", caller, e),
+            label: "invalid optimize_level value".to_string(),
+        }
+        .into())
+    })
+}
@@ -30,7 +30,7 @@ lazy_static! {
         let path = DEFAULT_DISTRIBUTION_TEMP_DIR.path();
 
         let dist = PYTHON_DISTRIBUTIONS
-            .find_distribution(env!("HOST"), &DistributionFlavor::Standalone)
+            .find_distribution(env!("HOST"), &DistributionFlavor::Standalone, None)
             .expect("target triple not supported");
 
         let logger = get_logger().expect("unable to construct logger");
@@ -44,7 +44,7 @@ lazy_static! {
         let path = DEFAULT_DISTRIBUTION_TEMP_DIR.path();
 
         let dist = PYTHON_DISTRIBUTIONS
-            .find_distribution(env!("HOST"), &DistributionFlavor::StandaloneDynamic)
+            .find_distribution(env!("HOST"), &DistributionFlavor::StandaloneDynamic, None)
             .expect("target triple not supported");
 
         let logger = get_logger().expect("unable to construct logger");
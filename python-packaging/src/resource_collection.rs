@@ -44,12 +44,27 @@ pub enum PythonResourcesPolicy {
     PreferInMemoryFallbackFilesystemRelative(String),
 }
 
+/// Path prefix for filesystem-relative resources when a policy doesn't specify one.
+const DEFAULT_RELATIVE_PATH_PREFIX: &str = "lib";
+
 impl TryFrom<&str> for PythonResourcesPolicy {
     type Error = anyhow::Error;
 
     fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let value = value.trim();
+
         if value == "in-memory-only" {
             Ok(PythonResourcesPolicy::InMemoryOnly)
+        } else if value == "filesystem-relative-only" {
+            Ok(PythonResourcesPolicy::FilesystemRelativeOnly(
+                DEFAULT_RELATIVE_PATH_PREFIX.to_string(),
+            ))
+        } else if value == "prefer-in-memory-fallback-filesystem-relative" {
+            Ok(
+                PythonResourcesPolicy::PreferInMemoryFallbackFilesystemRelative(
+                    DEFAULT_RELATIVE_PATH_PREFIX.to_string(),
+                ),
+            )
         } else if value.starts_with("filesystem-relative-only:") {
             let prefix = &value["filesystem-relative-only:".len()..];
 
@@ -83,6 +98,36 @@ impl Into<String> for &PythonResourcesPolicy {
     }
 }
 
+/// Describes a kind of data associated with a resource.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ResourceDataKind {
+    /// Python module source code.
+    Source,
+    /// Python module bytecode, at any optimization level.
+    Bytecode,
+    /// Non-module files in a Python package.
+    Resource,
+    /// Python extension module shared libraries.
+    Extension,
+}
+
+impl TryFrom<&str> for ResourceDataKind {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "source" => Ok(ResourceDataKind::Source),
+            "bytecode" => Ok(ResourceDataKind::Bytecode),
+            "resource" => Ok(ResourceDataKind::Resource),
+            "extension" => Ok(ResourceDataKind::Extension),
+            _ => Err(anyhow!(
+                "invalid resource kind: {}; must be one of source, bytecode, resource, or extension",
+                value
+            )),
+        }
+    }
+}
+
 /// Describes how Python module bytecode will be obtained.
 #[derive(Clone, Debug, PartialEq)]
 pub enum PythonModuleBytecodeProvider {
@@ -92,6 +137,16 @@ pub enum PythonModuleBytecodeProvider {
     FromSource(DataLocation),
 }
 
+impl PythonModuleBytecodeProvider {
+    /// Resolve the data backing this instance into memory.
+    pub fn to_memory(&self) -> Result<Self> {
+        Ok(match self {
+            Self::Provided(location) => Self::Provided(location.to_memory()?),
+            Self::FromSource(location) => Self::FromSource(location.to_memory()?),
+        })
+    }
+}
+
 /// Represents a Python resource entry before it is packaged.
 ///
 /// Instances hold the same fields as `Resource` except fields holding
@@ -254,6 +309,166 @@ impl<'a> TryFrom<&PrePackagedResource> for Resource<'a, u8> {
 }
 
 impl PrePackagedResource {
+    /// Obtain a copy of this resource with all data resolved into memory.
+    ///
+    /// Data locations are otherwise significant when comparing instances.
+    pub fn to_memory(&self) -> Result<Self> {
+        let location = |v: &Option<DataLocation>| -> Result<Option<DataLocation>> {
+            v.as_ref().map(|l| l.to_memory()).transpose()
+        };
+        let bytecode = |v: &Option<PythonModuleBytecodeProvider>| -> Result<_> {
+            v.as_ref().map(|p| p.to_memory()).transpose()
+        };
+        let relative_bytecode =
+            |v: &Option<(String, String, PythonModuleBytecodeProvider)>| -> Result<_> {
+                v.as_ref()
+                    .map(|(prefix, tag, p)| Ok((prefix.clone(), tag.clone(), p.to_memory()?)))
+                    .transpose()
+            };
+        let map = |v: &Option<BTreeMap<String, DataLocation>>| -> Result<_> {
+            v.as_ref()
+                .map(|m| {
+                    m.iter()
+                        .map(|(k, l)| Ok((k.clone(), l.to_memory()?)))
+                        .collect::<Result<BTreeMap<_, _>>>()
+                })
+                .transpose()
+        };
+        let relative_map =
+            |v: &Option<BTreeMap<String, (String, PathBuf, DataLocation)>>| -> Result<_> {
+                v.as_ref()
+                    .map(|m| {
+                        m.iter()
+                            .map(|(k, (prefix, path, l))| {
+                                Ok((k.clone(), (prefix.clone(), path.clone(), l.to_memory()?)))
+                            })
+                            .collect::<Result<BTreeMap<_, _>>>()
+                    })
+                    .transpose()
+            };
+
+        Ok(Self {
+            flavor: self.flavor,
+            name: self.name.clone(),
+            is_package: self.is_package,
+            is_namespace_package: self.is_namespace_package,
+            in_memory_source: location(&self.in_memory_source)?,
+            in_memory_bytecode: bytecode(&self.in_memory_bytecode)?,
+            in_memory_bytecode_opt1: bytecode(&self.in_memory_bytecode_opt1)?,
+            in_memory_bytecode_opt2: bytecode(&self.in_memory_bytecode_opt2)?,
+            in_memory_extension_module_shared_library: location(
+                &self.in_memory_extension_module_shared_library,
+            )?,
+            in_memory_resources: map(&self.in_memory_resources)?,
+            in_memory_distribution_resources: map(&self.in_memory_distribution_resources)?,
+            in_memory_shared_library: location(&self.in_memory_shared_library)?,
+            shared_library_dependency_names: self.shared_library_dependency_names.clone(),
+            relative_path_module_source: self
+                .relative_path_module_source
+                .as_ref()
+                .map(|(prefix, l)| -> Result<_> { Ok((prefix.clone(), l.to_memory()?)) })
+                .transpose()?,
+            relative_path_bytecode: relative_bytecode(&self.relative_path_bytecode)?,
+            relative_path_bytecode_opt1: relative_bytecode(&self.relative_path_bytecode_opt1)?,
+            relative_path_bytecode_opt2: relative_bytecode(&self.relative_path_bytecode_opt2)?,
+            relative_path_extension_module_shared_library: self
+                .relative_path_extension_module_shared_library
+                .as_ref()
+                .map(|(prefix, path, l)| -> Result<_> {
+                    Ok((prefix.clone(), path.clone(), l.to_memory()?))
+                })
+                .transpose()?,
+            relative_path_package_resources: relative_map(&self.relative_path_package_resources)?,
+            relative_path_distribution_resources: relative_map(
+                &self.relative_path_distribution_resources,
+            )?,
+            relative_path_shared_library: self
+                .relative_path_shared_library
+                .as_ref()
+                .map(|(prefix, l)| -> Result<_> { Ok((prefix.clone(), l.to_memory()?)) })
+                .transpose()?,
+        })
+    }
+
+    /// Remove data of a given kind from this resource.
+    ///
+    /// Data is removed from all locations.
+    pub fn remove_data(&mut self, kind: ResourceDataKind) {
+        match kind {
+            ResourceDataKind::Source => {
+                self.in_memory_source = None;
+                self.relative_path_module_source = None;
+            }
+            ResourceDataKind::Bytecode => {
+                self.in_memory_bytecode = None;
+                self.in_memory_bytecode_opt1 = None;
+                self.in_memory_bytecode_opt2 = None;
+                self.relative_path_bytecode = None;
+                self.relative_path_bytecode_opt1 = None;
+                self.relative_path_bytecode_opt2 = None;
+            }
+            ResourceDataKind::Resource => {
+                self.in_memory_resources = None;
+                self.relative_path_package_resources = None;
+            }
+            ResourceDataKind::Extension => {
+                self.in_memory_extension_module_shared_library = None;
+                self.relative_path_extension_module_shared_library = None;
+            }
+        }
+    }
+
+    /// Whether this resource has any data marked for in-memory loading.
+    pub fn has_in_memory_data(&self) -> bool {
+        self.in_memory_source.is_some()
+            || self.in_memory_bytecode.is_some()
+            || self.in_memory_bytecode_opt1.is_some()
+            || self.in_memory_bytecode_opt2.is_some()
+            || self.in_memory_extension_module_shared_library.is_some()
+            || self.in_memory_resources.is_some()
+            || self.in_memory_distribution_resources.is_some()
+            || self.in_memory_shared_library.is_some()
+    }
+
+    /// Whether this resource has any data marked for relative path loading.
+    pub fn has_relative_path_data(&self) -> bool {
+        self.relative_path_module_source.is_some()
+            || self.relative_path_bytecode.is_some()
+            || self.relative_path_bytecode_opt1.is_some()
+            || self.relative_path_bytecode_opt2.is_some()
+            || self.relative_path_extension_module_shared_library.is_some()
+            || self.relative_path_package_resources.is_some()
+            || self.relative_path_distribution_resources.is_some()
+            || self.relative_path_shared_library.is_some()
+    }
+
+    /// Whether this resource has bytecode that needs to be compiled from source.
+    pub fn has_bytecode_from_source(&self) -> bool {
+        let is_from_source = |provider: &PythonModuleBytecodeProvider| match provider {
+            PythonModuleBytecodeProvider::FromSource(_) => true,
+            PythonModuleBytecodeProvider::Provided(_) => false,
+        };
+
+        [
+            &self.in_memory_bytecode,
+            &self.in_memory_bytecode_opt1,
+            &self.in_memory_bytecode_opt2,
+        ]
+        .iter()
+        .any(|provider| provider.as_ref().map_or(false, is_from_source))
+            || [
+                &self.relative_path_bytecode,
+                &self.relative_path_bytecode_opt1,
+                &self.relative_path_bytecode_opt2,
+            ]
+            .iter()
+            .any(|entry| {
+                entry
+                    .as_ref()
+                    .map_or(false, |(_, _, provider)| is_from_source(provider))
+            })
+    }
+
     /// Derive additional file installs to perform for filesystem-based resources.
     ///
     /// Returns 3-tuples denoting the relative resource path, data to materialize there,
@@ -475,6 +690,79 @@ impl<'a> PreparedPythonResources<'a> {
             None,
         )
     }
+
+    /// Obtain C source code defining an array of frozen modules.
+    ///
+    /// The emitted array is of CPython's `struct _frozen` type and holds an
+    /// entry for every resource having in-memory bytecode (at optimization
+    /// level 0). It is suitable for assigning to `PyImport_FrozenModules`.
+    /// Like CPython, packages are denoted by a negative size.
+    pub fn to_frozen_c_array(&self, variable_name: &str) -> Result<String> {
+        if variable_name.is_empty()
+            || variable_name.starts_with(|c: char| c.is_ascii_digit())
+            || !variable_name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_')
+        {
+            return Err(anyhow!("{} is not a valid C identifier", variable_name));
+        }
+
+        let mut data = String::new();
+        let mut entries = String::new();
+
+        for (i, resource) in self
+            .resources
+            .values()
+            .filter(|r| r.in_memory_bytecode.is_some())
+            .enumerate()
+        {
+            let bytecode = resource.in_memory_bytecode.as_ref().unwrap();
+
+            data.push_str(&format!(
+                "static const unsigned char {}_{}[] = {{",
+                variable_name, i
+            ));
+            for (j, byte) in bytecode.iter().enumerate() {
+                if j % 16 == 0 {
+                    data.push_str("\n    ");
+                }
+                data.push_str(&format!("{},", byte));
+            }
+            data.push_str("\n};\n\n");
+
+            entries.push_str(&format!(
+                "    {{{}, {}_{}, {}{}}},\n",
+                c_string_literal(&resource.name),
+                variable_name,
+                i,
+                if resource.is_package { "-" } else { "" },
+                bytecode.len()
+            ));
+        }
+
+        Ok(format!(
+            "#include \"Python.h\"\n\n{}const struct _frozen {}[] = {{\n{}    {{0, 0, 0}}\n}};\n",
+            data, variable_name, entries
+        ))
+    }
+}
+
+/// Represent a string as a C string literal.
+fn c_string_literal(value: &str) -> String {
+    let mut s = String::from("\"");
+
+    for b in value.bytes() {
+        match b {
+            b'"' => s.push_str("\\\""),
+            b'\\' => s.push_str("\\\\"),
+            0x20..=0x7e => s.push(b as char),
+            _ => s.push_str(&format!("\\{:03o}", b)),
+        }
+    }
+
+    s.push('"');
+
+    s
 }
 
 /// Type used to collect Python resources to they can be serialized.
@@ -511,6 +799,39 @@ impl PythonResourceCollector {
         &self.policy
     }
 
+    /// Obtain the Python bytecode cache tag for this collector.
+    pub fn get_cache_tag(&self) -> &str {
+        &self.cache_tag
+    }
+
+    /// Whether preparing resources requires compiling bytecode from source.
+    ///
+    /// This includes bytecode for parent packages that will be derived
+    /// automatically.
+    pub fn requires_bytecode_compilation(&self) -> Result<bool> {
+        let mut resources = self.resources.clone();
+        populate_parent_packages(&mut resources)?;
+
+        Ok(resources.values().any(|r| r.has_bytecode_from_source()))
+    }
+
+    /// Validate that provided bytecode targets this collector's cache tag.
+    ///
+    /// Bytecode compiled for another Python version has a different magic
+    /// number and can't be loaded by the interpreter we're collecting for.
+    fn check_bytecode_cache_tag(&self, module: &PythonModuleBytecode) -> Result<()> {
+        if module.cache_tag == self.cache_tag {
+            Ok(())
+        } else {
+            Err(anyhow!(
+                "bytecode for {} has cache tag {}; expected {}",
+                module.name,
+                module.cache_tag,
+                self.cache_tag
+            ))
+        }
+    }
+
     /// Validate that a resource add in the specified location is allowed.
     pub fn check_policy(&self, location: ResourceLocation) -> Result<()> {
         match self.policy {
@@ -548,6 +869,149 @@ impl PythonResourceCollector {
         Ok(())
     }
 
+    /// Apply a filter function on resources and remove data of specific kinds.
+    ///
+    /// If the filter function returns false, data of the kinds in `kinds` is
+    /// removed from the resource. Other data is always preserved. Resources
+    /// left without any data are removed, since they would otherwise advertise
+    /// a module that can't be loaded.
+    pub fn filter_resource_data_mut<F>(
+        &mut self,
+        kinds: &[ResourceDataKind],
+        filter: F,
+    ) -> Result<()>
+    where
+        F: Fn(&PrePackagedResource) -> bool,
+    {
+        let mut emptied = Vec::new();
+
+        for (name, resource) in self.resources.iter_mut() {
+            if !filter(resource) {
+                let had_data = resource.has_in_memory_data() || resource.has_relative_path_data();
+
+                for kind in kinds {
+                    resource.remove_data(*kind);
+                }
+
+                if had_data && !resource.has_in_memory_data() && !resource.has_relative_path_data()
+                {
+                    emptied.push(name.clone());
+                }
+            }
+        }
+
+        for name in emptied {
+            self.resources.remove(&name);
+        }
+
+        Ok(())
+    }
+
+    /// Remove a resource from this collection.
+    ///
+    /// All data associated with the named entity is removed, regardless of
+    /// its flavor or location. Returns whether an entry was removed.
+    pub fn remove_resource(&mut self, name: &str) -> bool {
+        self.resources.remove(name).is_some()
+    }
+
+    /// Whether a resource with the given name is present in this collection.
+    pub fn has_resource(&self, name: &str) -> bool {
+        self.resources.contains_key(name)
+    }
+
+    /// Obtain the number of resources in this collection.
+    ///
+    /// Each named entity counts once, regardless of how many kinds of data
+    /// are stored for it.
+    pub fn len(&self) -> usize {
+        self.resources.len()
+    }
+
+    /// Whether this collection has no resources.
+    pub fn is_empty(&self) -> bool {
+        self.resources.is_empty()
+    }
+
+    /// Whether this instance holds the same resources as another.
+    ///
+    /// Resources are compared by name, flavor, and data content. Whether data
+    /// is backed by a file or memory and the order resources were added in are
+    /// not significant.
+    pub fn has_same_resources(&self, other: &PythonResourceCollector) -> Result<bool> {
+        if self.resources.len() != other.resources.len() {
+            return Ok(false);
+        }
+
+        for ((name, a), (other_name, b)) in self.resources.iter().zip(other.resources.iter()) {
+            if name != other_name || a.to_memory()? != b.to_memory()? {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Add resources from another collection to this one.
+    ///
+    /// Every resource in `other` must be allowed by this instance's policy.
+    /// If a resource with the same name is already present, an error is
+    /// returned unless `overwrite` is true, in which case the existing
+    /// resource is replaced. On error, this instance is not modified.
+    pub fn merge(&mut self, other: &PythonResourceCollector, overwrite: bool) -> Result<()> {
+        for (name, resource) in &other.resources {
+            if resource.has_in_memory_data() {
+                self.check_policy(ResourceLocation::InMemory)?;
+            }
+            if resource.has_relative_path_data() {
+                self.check_policy(ResourceLocation::RelativePath)?;
+            }
+            if !overwrite && self.resources.contains_key(name) {
+                return Err(anyhow!("resource {} is already present", name));
+            }
+        }
+
+        for (name, resource) in &other.resources {
+            self.resources.insert(name.clone(), resource.clone());
+        }
+
+        Ok(())
+    }
+
+    /// Find packages having resources attached but no module data.
+    ///
+    /// Package resources are registered against their leaf package. If that
+    /// package was never added (and isn't the parent of an added module), the
+    /// resources can't be loaded at run-time because the package can't be
+    /// imported.
+    ///
+    /// Returns the names of such packages.
+    pub fn find_orphaned_package_resources(&self) -> Vec<String> {
+        self.resources
+            .values()
+            .filter(|r| {
+                r.flavor == ResourceFlavor::Module
+                    && (r.in_memory_resources.is_some()
+                        || r.relative_path_package_resources.is_some())
+                    && r.in_memory_source.is_none()
+                    && r.in_memory_bytecode.is_none()
+                    && r.in_memory_bytecode_opt1.is_none()
+                    && r.in_memory_bytecode_opt2.is_none()
+                    && r.in_memory_extension_module_shared_library.is_none()
+                    && r.relative_path_module_source.is_none()
+                    && r.relative_path_bytecode.is_none()
+                    && r.relative_path_bytecode_opt1.is_none()
+                    && r.relative_path_bytecode_opt2.is_none()
+                    && r.relative_path_extension_module_shared_library.is_none()
+            })
+            .filter(|r| {
+                let prefix = format!("{}.", r.name);
+                !self.resources.keys().any(|k| k.starts_with(&prefix))
+            })
+            .map(|r| r.name.clone())
+            .collect()
+    }
+
     /// Obtain `PythonModuleSource` in this instance.
     pub fn get_in_memory_module_sources(&self) -> BTreeMap<String, PythonModuleSource> {
         BTreeMap::from_iter(self.resources.iter().filter_map(|(name, module)| {
@@ -638,6 +1102,7 @@ impl PythonResourceCollector {
         module: &PythonModuleBytecode,
     ) -> Result<()> {
         self.check_policy(ResourceLocation::InMemory)?;
+        self.check_bytecode_cache_tag(module)?;
 
         let entry = self
             .resources
@@ -711,12 +1176,14 @@ impl PythonResourceCollector {
         Ok(())
     }
 
+    /// Add Python module bytecode to be loaded from the filesystem relative to some entity.
     pub fn add_relative_path_python_module_bytecode(
         &mut self,
         module: &PythonModuleBytecode,
         prefix: &str,
     ) -> Result<()> {
         self.check_policy(ResourceLocation::RelativePath)?;
+        self.check_bytecode_cache_tag(module)?;
 
         let entry = self
             .resources
@@ -763,6 +1230,8 @@ impl PythonResourceCollector {
     }
 
     /// Add a Python bytecode module from source to be loaded from the filesystem relative to some entity.
+    ///
+    /// Bytecode files are named using this collection's cache tag.
     pub fn add_relative_path_python_module_bytecode_from_source(
         &mut self,
         module: &PythonModuleBytecodeFromSource,
@@ -784,21 +1253,21 @@ impl PythonResourceCollector {
             BytecodeOptimizationLevel::Zero => {
                 entry.relative_path_bytecode = Some((
                     prefix.to_string(),
-                    module.cache_tag.clone(),
+                    self.cache_tag.clone(),
                     PythonModuleBytecodeProvider::FromSource(module.source.clone()),
                 ))
             }
             BytecodeOptimizationLevel::One => {
                 entry.relative_path_bytecode_opt1 = Some((
                     prefix.to_string(),
-                    module.cache_tag.clone(),
+                    self.cache_tag.clone(),
                     PythonModuleBytecodeProvider::FromSource(module.source.clone()),
                 ))
             }
             BytecodeOptimizationLevel::Two => {
                 entry.relative_path_bytecode_opt2 = Some((
                     prefix.to_string(),
-                    module.cache_tag.clone(),
+                    self.cache_tag.clone(),
                     PythonModuleBytecodeProvider::FromSource(module.source.clone()),
                 ))
             }
@@ -1332,6 +1801,18 @@ mod tests {
             PythonResourcesPolicy::try_from("prefer-in-memory-fallback-filesystem-relative:lib")?,
             PythonResourcesPolicy::PreferInMemoryFallbackFilesystemRelative("lib".to_string())
         );
+        assert_eq!(
+            PythonResourcesPolicy::try_from("filesystem-relative-only")?,
+            PythonResourcesPolicy::FilesystemRelativeOnly("lib".to_string())
+        );
+        assert_eq!(
+            PythonResourcesPolicy::try_from("prefer-in-memory-fallback-filesystem-relative")?,
+            PythonResourcesPolicy::PreferInMemoryFallbackFilesystemRelative("lib".to_string())
+        );
+        assert_eq!(
+            PythonResourcesPolicy::try_from(" in-memory-only\n")?,
+            PythonResourcesPolicy::InMemoryOnly
+        );
         assert_eq!(
             PythonResourcesPolicy::try_from("foo")
                 .unwrap_err()
@@ -1598,6 +2079,96 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_remove_resource() -> Result<()> {
+        let mut r =
+            PythonResourceCollector::new(&PythonResourcesPolicy::InMemoryOnly, DEFAULT_CACHE_TAG);
+        r.add_in_memory_python_module_source(&PythonModuleSource {
+            name: "foo".to_string(),
+            source: DataLocation::Memory(vec![42]),
+            is_package: false,
+            cache_tag: DEFAULT_CACHE_TAG.to_string(),
+        })?;
+
+        assert!(r.has_resource("foo"));
+        assert!(r.remove_resource("foo"));
+        assert!(!r.has_resource("foo"));
+        assert!(r.resources.is_empty());
+        assert!(!r.remove_resource("foo"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge() -> Result<()> {
+        let source = |value: u8| PythonModuleSource {
+            name: "foo".to_string(),
+            source: DataLocation::Memory(vec![value]),
+            is_package: false,
+            cache_tag: DEFAULT_CACHE_TAG.to_string(),
+        };
+
+        let mut a =
+            PythonResourceCollector::new(&PythonResourcesPolicy::InMemoryOnly, DEFAULT_CACHE_TAG);
+        let mut b =
+            PythonResourceCollector::new(&PythonResourcesPolicy::InMemoryOnly, DEFAULT_CACHE_TAG);
+        b.add_in_memory_python_module_source(&source(42))?;
+
+        a.merge(&b, false)?;
+        assert!(a.has_same_resources(&b)?);
+
+        let mut c =
+            PythonResourceCollector::new(&PythonResourcesPolicy::InMemoryOnly, DEFAULT_CACHE_TAG);
+        c.add_in_memory_python_module_source(&source(43))?;
+
+        assert!(a.merge(&c, false).is_err());
+        assert!(a.has_same_resources(&b)?);
+        a.merge(&c, true)?;
+        assert!(a.has_same_resources(&c)?);
+
+        let mut relative = PythonResourceCollector::new(
+            &PythonResourcesPolicy::FilesystemRelativeOnly("".to_string()),
+            DEFAULT_CACHE_TAG,
+        );
+        assert!(relative.merge(&b, false).is_err());
+        assert!(relative.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_has_same_resources_data_location() -> Result<()> {
+        let td = tempdir::TempDir::new("pyoxidizer-test")?;
+        let path = td.path().join("foo.py");
+        std::fs::write(&path, b"import io")?;
+
+        let source = |location: DataLocation| PythonModuleSource {
+            name: "foo".to_string(),
+            source: location,
+            is_package: false,
+            cache_tag: DEFAULT_CACHE_TAG.to_string(),
+        };
+
+        let mut a =
+            PythonResourceCollector::new(&PythonResourcesPolicy::InMemoryOnly, DEFAULT_CACHE_TAG);
+        a.add_in_memory_python_module_source(&source(DataLocation::Path(path)))?;
+
+        let mut b =
+            PythonResourceCollector::new(&PythonResourcesPolicy::InMemoryOnly, DEFAULT_CACHE_TAG);
+        b.add_in_memory_python_module_source(&source(DataLocation::Memory(b"import io".to_vec())))?;
+
+        assert!(a.has_same_resources(&b)?);
+        assert!(b.has_same_resources(&a)?);
+
+        let mut c =
+            PythonResourceCollector::new(&PythonResourcesPolicy::InMemoryOnly, DEFAULT_CACHE_TAG);
+        c.add_in_memory_python_module_source(&source(DataLocation::Memory(b"import os".to_vec())))?;
+
+        assert!(!a.has_same_resources(&c)?);
+
+        Ok(())
+    }
+
     #[test]
     fn test_add_relative_path_source_module() -> Result<()> {
         let mut r = PythonResourceCollector::new(
@@ -1663,6 +2234,73 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_add_bytecode_module_cache_tag_mismatch() -> Result<()> {
+        let mut r = PythonResourceCollector::new(
+            &PythonResourcesPolicy::PreferInMemoryFallbackFilesystemRelative("".to_string()),
+            "cpython-99",
+        );
+        let module = PythonModuleBytecode::new(
+            "foo",
+            BytecodeOptimizationLevel::Zero,
+            false,
+            DEFAULT_CACHE_TAG,
+            &vec![42],
+        );
+
+        let err = r
+            .add_in_memory_python_module_bytecode(&module)
+            .unwrap_err()
+            .to_string();
+        assert_eq!(
+            err,
+            format!(
+                "bytecode for foo has cache tag {}; expected cpython-99",
+                DEFAULT_CACHE_TAG
+            )
+        );
+        assert!(r
+            .add_relative_path_python_module_bytecode(&module, "")
+            .is_err());
+        assert!(r.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_requires_bytecode_compilation() -> Result<()> {
+        let mut r = PythonResourceCollector::new(
+            &PythonResourcesPolicy::FilesystemRelativeOnly("".to_string()),
+            DEFAULT_CACHE_TAG,
+        );
+        r.add_relative_path_python_module_bytecode(
+            &PythonModuleBytecode::new(
+                "foo",
+                BytecodeOptimizationLevel::Zero,
+                false,
+                DEFAULT_CACHE_TAG,
+                &vec![42],
+            ),
+            "",
+        )?;
+        assert!(!r.requires_bytecode_compilation()?);
+
+        // Bytecode for the parent package is compiled from source.
+        r.add_relative_path_python_module_bytecode(
+            &PythonModuleBytecode::new(
+                "foo.bar",
+                BytecodeOptimizationLevel::Zero,
+                false,
+                DEFAULT_CACHE_TAG,
+                &vec![42],
+            ),
+            "",
+        )?;
+        assert!(r.requires_bytecode_compilation()?);
+
+        Ok(())
+    }
+
     #[test]
     fn test_add_in_memory_bytecode_module_from_source() -> Result<()> {
         let mut r =
@@ -1750,6 +2388,173 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_to_frozen_c_array() -> Result<()> {
+        let mut resources = BTreeMap::new();
+        resources.insert(
+            "foo".to_string(),
+            Resource {
+                flavor: ResourceFlavor::Module,
+                name: Cow::Owned("foo".to_string()),
+                is_package: true,
+                in_memory_bytecode: Some(Cow::Owned(vec![1, 2, 3])),
+                ..Resource::default()
+            },
+        );
+        resources.insert(
+            "bar".to_string(),
+            Resource {
+                flavor: ResourceFlavor::Module,
+                name: Cow::Owned("bar".to_string()),
+                in_memory_source: Some(Cow::Owned(vec![42])),
+                ..Resource::default()
+            },
+        );
+
+        let prepared = PreparedPythonResources {
+            resources,
+            extra_files: vec![],
+        };
+
+        let c = prepared.to_frozen_c_array("frozen_modules")?;
+        assert!(c.contains("static const unsigned char frozen_modules_0[] = {\n    1,2,3,\n};"));
+        assert!(c.contains("const struct _frozen frozen_modules[] = {\n"));
+        assert!(c.contains("    {\"foo\", frozen_modules_0, -3},\n"));
+        assert!(!c.contains("\"bar\""));
+
+        assert!(prepared.to_frozen_c_array("not-valid").is_err());
+        assert!(prepared.to_frozen_c_array("0foo").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_c_string_literal() {
+        assert_eq!(c_string_literal("foo.bar"), "\"foo.bar\"");
+        assert_eq!(c_string_literal("a\"b\\c\n"), "\"a\\\"b\\\\c\\012\"");
+    }
+
+    #[test]
+    fn test_filter_resource_data() -> Result<()> {
+        let mut r =
+            PythonResourceCollector::new(&PythonResourcesPolicy::InMemoryOnly, DEFAULT_CACHE_TAG);
+        for name in &["foo", "bar"] {
+            r.add_in_memory_python_module_source(&PythonModuleSource {
+                name: name.to_string(),
+                source: DataLocation::Memory(vec![42]),
+                is_package: false,
+                cache_tag: DEFAULT_CACHE_TAG.to_string(),
+            })?;
+            r.add_in_memory_python_module_bytecode_from_source(&PythonModuleBytecodeFromSource {
+                name: name.to_string(),
+                source: DataLocation::Memory(vec![42]),
+                optimize_level: BytecodeOptimizationLevel::Zero,
+                is_package: false,
+                cache_tag: DEFAULT_CACHE_TAG.to_string(),
+            })?;
+        }
+
+        r.filter_resource_data_mut(&[ResourceDataKind::Bytecode], |r| r.name == "foo")?;
+
+        let foo = r.resources.get("foo").unwrap();
+        assert!(foo.in_memory_source.is_some());
+        assert!(foo.in_memory_bytecode.is_some());
+
+        let bar = r.resources.get("bar").unwrap();
+        assert_eq!(bar.in_memory_source, Some(DataLocation::Memory(vec![42])));
+        assert!(bar.in_memory_bytecode.is_none());
+
+        assert_eq!(
+            ResourceDataKind::try_from("bytecode")?,
+            ResourceDataKind::Bytecode
+        );
+        assert!(ResourceDataKind::try_from("bad").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_filter_resource_data_removes_empty() -> Result<()> {
+        let mut r =
+            PythonResourceCollector::new(&PythonResourcesPolicy::InMemoryOnly, DEFAULT_CACHE_TAG);
+        r.add_in_memory_python_module_source(&PythonModuleSource {
+            name: "foo".to_string(),
+            source: DataLocation::Memory(vec![42]),
+            is_package: false,
+            cache_tag: DEFAULT_CACHE_TAG.to_string(),
+        })?;
+        r.add_in_memory_python_module_bytecode_from_source(&PythonModuleBytecodeFromSource {
+            name: "foo".to_string(),
+            source: DataLocation::Memory(vec![42]),
+            optimize_level: BytecodeOptimizationLevel::Zero,
+            is_package: false,
+            cache_tag: DEFAULT_CACHE_TAG.to_string(),
+        })?;
+        r.add_builtin_python_extension_module(&PythonExtensionModule {
+            name: "_io".to_string(),
+            init_fn: Some("PyInit__io".to_string()),
+            extension_file_suffix: "".to_string(),
+            extension_data: None,
+            object_file_data: vec![],
+            is_package: false,
+            libraries: vec![],
+            library_dirs: vec![],
+        })?;
+
+        r.filter_resource_data_mut(&[ResourceDataKind::Bytecode], |_| false)?;
+        assert!(r.has_resource("foo"));
+
+        r.filter_resource_data_mut(
+            &[ResourceDataKind::Source, ResourceDataKind::Extension],
+            |_| false,
+        )?;
+        assert!(!r.has_resource("foo"));
+
+        // Resources that never had data are kept.
+        assert!(r.has_resource("_io"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_orphaned_package_resources() -> Result<()> {
+        let mut r =
+            PythonResourceCollector::new(&PythonResourcesPolicy::InMemoryOnly, DEFAULT_CACHE_TAG);
+        r.add_in_memory_python_package_resource(&PythonPackageResource {
+            leaf_package: "foo".to_string(),
+            relative_name: "resource.txt".to_string(),
+            data: DataLocation::Memory(vec![42]),
+        })?;
+
+        assert_eq!(r.find_orphaned_package_resources(), vec!["foo".to_string()]);
+
+        r.add_in_memory_python_module_source(&PythonModuleSource {
+            name: "foo".to_string(),
+            source: DataLocation::Memory(vec![]),
+            is_package: true,
+            cache_tag: DEFAULT_CACHE_TAG.to_string(),
+        })?;
+
+        assert!(r.find_orphaned_package_resources().is_empty());
+
+        // Resources on a package implicitly created as a parent are fine.
+        r.add_in_memory_python_package_resource(&PythonPackageResource {
+            leaf_package: "bar".to_string(),
+            relative_name: "resource.txt".to_string(),
+            data: DataLocation::Memory(vec![42]),
+        })?;
+        r.add_in_memory_python_module_source(&PythonModuleSource {
+            name: "bar.baz".to_string(),
+            source: DataLocation::Memory(vec![]),
+            is_package: false,
+            cache_tag: DEFAULT_CACHE_TAG.to_string(),
+        })?;
+
+        assert!(r.find_orphaned_package_resources().is_empty());
+
+        Ok(())
+    }
+
     #[test]
     fn test_add_relative_path_extension_module() -> Result<()> {
         let mut c = PythonResourceCollector::new(